@@ -12,6 +12,15 @@ maxOutcomes=${maxOutcomes:-8}
 defaultChallengeWindowDuration=${defaultChallengeWindowDuration:-120000000000}
 minInitialChallengeWindowDuration=${minInitialChallengeWindowDuration:-120000000000}
 finalArbitratorInvokeAmount=${finalArbitratorInvokeAmount:-100000000000000000000000000}
+slashUnrevealedStake=${slashUnrevealedStake:-false}
+maxSettlementTimeOffset=${maxSettlementTimeOffset:-31536000000000000}
+requestExpiryDuration=${requestExpiryDuration:-2592000000000000}
+bondMultiplier=${bondMultiplier:-20000}
+arbitrationTimeout=${arbitrationTimeout:-0}
+arbitrationFallback=${arbitrationFallback:-LastBondedOutcome}
+claimWindowDuration=${claimWindowDuration:-2592000000000000}
+treasury=${treasury:-$gov}
+configChangeDelay=${configChangeDelay:-0}
 # resolutionFeePercentage=${resolutionFeePercentage:-5000}
 fluxMarketCap=${fluxMarketCap:-10000000000000}
 totalValueStaked=${totalValueStaked:-1000}
@@ -28,4 +37,4 @@ while [ $# -gt 0 ]; do
   shift
 done
 
-NEAR_ENV=$network near deploy --accountId $accountId --wasmFile ./res/oracle.wasm --initFunction new --initArgs '{ "config": { "gov": "'$gov'", "final_arbitrator": "'$finalArbitrator'", "stake_token": "'$stakeToken'", "payment_token": "'$paymentToken'", "validity_bond": "'$validityBond'", "max_outcomes": '$maxOutcomes', "default_challenge_window_duration": "'$defaultChallengeWindowDuration'", "min_initial_challenge_window_duration": "'$minInitialChallengeWindowDuration'", "final_arbitrator_invoke_amount": "'$finalArbitratorInvokeAmount'", "resolution_fee_percentage": '$resolutionFeePercentage', "fee": {"flux_market_cap": "'$fluxMarketCap'", "total_value_staked":"'$totalValueStaked'", "resolution_fee_percentage": '$resolutionFeePercentage' } } }'
+NEAR_ENV=$network near deploy --accountId $accountId --wasmFile ./res/oracle.wasm --initFunction new --initArgs '{ "config": { "gov": "'$gov'", "final_arbitrator": "'$finalArbitrator'", "stake_token": "'$stakeToken'", "payment_token": "'$paymentToken'", "validity_bond": "'$validityBond'", "max_outcomes": '$maxOutcomes', "default_challenge_window_duration": "'$defaultChallengeWindowDuration'", "min_initial_challenge_window_duration": "'$minInitialChallengeWindowDuration'", "final_arbitrator_invoke_amount": "'$finalArbitratorInvokeAmount'", "slash_unrevealed_stake": '$slashUnrevealedStake', "max_settlement_time_offset": "'$maxSettlementTimeOffset'", "request_expiry_duration": "'$requestExpiryDuration'", "bond_escalation": { "Multiplier": '$bondMultiplier' }, "arbitration_council": null, "arbitration_timeout": "'$arbitrationTimeout'", "arbitration_fallback": "'$arbitrationFallback'", "claim_window_duration": "'$claimWindowDuration'", "treasury": "'$treasury'", "requestor_bounds": null, "config_change_delay": "'$configChangeDelay'", "resolution_fee_percentage": '$resolutionFeePercentage', "fee": {"flux_market_cap": "'$fluxMarketCap'", "total_value_staked":"'$totalValueStaked'", "resolution_fee_percentage": '$resolutionFeePercentage' } } }'
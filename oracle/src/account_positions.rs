@@ -0,0 +1,127 @@
+use crate::*;
+
+use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
+use near_sdk::serde::{ Serialize, Deserialize };
+use near_sdk::AccountId;
+use near_sdk::collections::UnorderedMap;
+
+use crate::types::*;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AccountPosition {
+    pub round: u16,
+    pub outcome: Outcome,
+    pub amount: Balance,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RequestPositions {
+    pub positions: Vec<AccountPosition>,
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountPositionSummary {
+    pub round: u16,
+    pub outcome: Outcome,
+    pub amount: WrappedBalance,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestPositionsSummary {
+    pub request_id: U64,
+    pub positions: Vec<AccountPositionSummary>,
+    pub claimable: bool, // `DataRequest` is finalized and the account hasn't claimed yet
+    pub claimed: bool,
+}
+
+impl Contract {
+    fn get_request_positions(&self, account_id: &AccountId) -> UnorderedMap<u64, RequestPositions> {
+        self.account_positions
+            .get(account_id)
+            .unwrap_or_else(|| UnorderedMap::new(format!("ap:{}", account_id).as_bytes().to_vec()))
+    }
+
+    // @notice Adds `amount` to the position of `account_id` in `round` on `outcome`
+    pub fn add_account_position(&mut self, account_id: &AccountId, request_id: u64, round: u16, outcome: &Outcome, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        let mut request_positions = self.get_request_positions(account_id);
+        let mut positions = request_positions.get(&request_id).unwrap_or(RequestPositions {
+            positions: Vec::new(),
+            claimed: false,
+        });
+
+        match positions.positions.iter_mut().find(|position| position.round == round && &position.outcome == outcome) {
+            Some(position) => position.amount += amount,
+            None => positions.positions.push(AccountPosition {
+                round,
+                outcome: outcome.clone(),
+                amount,
+            })
+        };
+
+        request_positions.insert(&request_id, &positions);
+        self.account_positions.insert(account_id, &request_positions);
+    }
+
+    // @notice Subtracts `amount` from the position of `account_id` in `round` on `outcome`, empty positions are removed
+    pub fn remove_account_position(&mut self, account_id: &AccountId, request_id: u64, round: u16, outcome: &Outcome, amount: Balance) {
+        let mut request_positions = self.get_request_positions(account_id);
+        let mut positions = match request_positions.get(&request_id) {
+            Some(positions) => positions,
+            None => return
+        };
+
+        if let Some(position) = positions.positions.iter_mut().find(|position| position.round == round && &position.outcome == outcome) {
+            position.amount -= std::cmp::min(position.amount, amount);
+        }
+        positions.positions.retain(|position| position.amount > 0);
+
+        if positions.positions.is_empty() {
+            request_positions.remove(&request_id);
+        } else {
+            request_positions.insert(&request_id, &positions);
+        }
+        self.account_positions.insert(account_id, &request_positions);
+    }
+
+    pub fn mark_account_positions_claimed(&mut self, account_id: &AccountId, request_id: u64) {
+        let mut request_positions = self.get_request_positions(account_id);
+        if let Some(mut positions) = request_positions.get(&request_id) {
+            positions.claimed = true;
+            request_positions.insert(&request_id, &positions);
+            self.account_positions.insert(account_id, &request_positions);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_account_positions(&self, account_id: AccountId, from_index: U64, limit: U64) -> Vec<RequestPositionsSummary> {
+        let request_positions = self.get_request_positions(&account_id);
+        let request_ids = request_positions.keys_as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i.saturating_add(limit.into()), request_ids.len()))
+            .map(|index| {
+                let request_id = request_ids.get(index).unwrap();
+                let positions = request_positions.get(&request_id).unwrap();
+                let finalized = self.data_requests.get(request_id).unwrap().finalized_outcome.is_some();
+
+                RequestPositionsSummary {
+                    request_id: U64(request_id),
+                    positions: positions.positions.iter().map(|position| AccountPositionSummary {
+                        round: position.round,
+                        outcome: position.outcome.clone(),
+                        amount: U128(position.amount),
+                    }).collect(),
+                    claimable: finalized && !positions.claimed,
+                    claimed: positions.claimed,
+                }
+            })
+            .collect()
+    }
+}
@@ -1,14 +1,16 @@
 use crate::*;
-use crate::data_request::DataRequestDataType;
+use crate::data_request::{ DataRequestDataType, NumberTolerance, PERCENTAGE_DIVISOR };
+use crate::target_contract_handler::TargetContract;
+use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::serde::{ Serialize, Deserialize };
 use types::*;
 
 const MAX_SOURCES: u8 = 8;
 const MAX_TAGS: u8 = 8;
-const MIN_OUTCOMES: u8 = 2;
-const MIN_PERIOD_MULTIPLIER: u64 = 3;
+pub const MIN_OUTCOMES: u8 = 2;
+pub const MIN_PERIOD_MULTIPLIER: u64 = 3;
 
-#[derive(Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
 pub struct NewDataRequestArgs {
     pub sources: Vec<data_request::Source>,
     pub tags: Option<Vec<String>>,
@@ -17,11 +19,17 @@ pub struct NewDataRequestArgs {
     pub challenge_period: WrappedTimestamp,
     pub data_type: DataRequestDataType,
     pub creator: AccountId,
+    pub reveal_period: Option<WrappedTimestamp>, // Enables commit-reveal staking, challenge period is used for commits and this period for reveals
+    pub tolerance: Option<NumberTolerance>, // Only for `DataRequestDataType::Number`, answers inside this band bond together
+    pub settlement_time: Option<WrappedTimestamp>, // Time at which the answer becomes knowable, staking is blocked until then
+    pub expiry_fallback: Option<Outcome>, // Outcome to finalize with when the request expires, defaults to `Outcome::Invalid`
+    pub target_contract: Option<TargetContract>, // Contract that receives the outcome instead of, or next to, the requestor
 }
 
 impl Contract {
-    pub fn dr_validate(&self, data_request: &NewDataRequestArgs) {
-        let config = self.get_config();
+    // @notice validates against the current config with the overrides of `requestor` applied
+    pub fn dr_validate(&self, data_request: &NewDataRequestArgs, requestor: &Requestor) {
+        let config = self.get_config().for_requestor(requestor);
         let challenge_period: u64 = data_request.challenge_period.into();
         let default_challenge_window_duration: u64 = config.default_challenge_window_duration.into();
         let min_initial_challenge_window_duration: u64 = config.min_initial_challenge_window_duration.into();
@@ -44,6 +52,36 @@ impl Contract {
             MIN_OUTCOMES,
             config.max_outcomes
         );
+        if let Some(reveal_period) = data_request.reveal_period {
+            let reveal_period: u64 = reveal_period.into();
+            assert!(reveal_period >= min_initial_challenge_window_duration, "Reveal period shorter than minimum reveal period of {}", min_initial_challenge_window_duration);
+            assert!(reveal_period <= default_challenge_window_duration * MIN_PERIOD_MULTIPLIER, "Reveal period exceeds maximum reveal period of {}", default_challenge_window_duration * MIN_PERIOD_MULTIPLIER);
+        }
+        if let Some(settlement_time) = data_request.settlement_time {
+            let max_settlement_time_offset: u64 = config.max_settlement_time_offset.into();
+            assert!(
+                u64::from(settlement_time) <= env::block_timestamp() + max_settlement_time_offset,
+                "Settlement time exceeds maximum settlement time offset of {}",
+                max_settlement_time_offset
+            );
+        }
+        if let Some(tolerance) = &data_request.tolerance {
+            match data_request.data_type {
+                DataRequestDataType::Number(_) => (),
+                DataRequestDataType::String => panic!("Tolerance can only be set for number data requests")
+            };
+            match tolerance {
+                NumberTolerance::Absolute(width) => assert!(u128::from(*width) > 0, "Absolute tolerance can't be 0"),
+                NumberTolerance::Relative(percentage) => assert!(
+                    *percentage > 0 && *percentage <= PERCENTAGE_DIVISOR,
+                    "Relative tolerance should be between 1 and {}",
+                    PERCENTAGE_DIVISOR
+                )
+            };
+        }
+        if let Some(target_contract) = &data_request.target_contract {
+            target_contract.assert_valid();
+        }
     }
 }
 
@@ -53,6 +91,12 @@ pub struct StakeDataRequestArgs {
     pub outcome: Outcome
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CommitDataRequestArgs {
+    pub id: U64,
+    pub commitment: String // hex encoded sha256 of `{account_id}:{outcome as json}:{salt}`
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ChallengeDataRequestArgs {
     pub id: U64,
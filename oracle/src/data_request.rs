@@ -3,21 +3,28 @@ use crate::*;
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::json_types::{U64, U128};
 use near_sdk::serde::{ Deserialize, Serialize };
-use near_sdk::{ env, Balance, AccountId, PromiseOrValue, Promise, ext_contract };
-use near_sdk::collections::{ Vector };
+use near_sdk::{ env, Balance, AccountId, PromiseOrValue, PromiseResult, Promise, StorageUsage, ext_contract };
+use near_sdk::collections::{ Vector, UnorderedSet };
 
 use crate::resolution_window::*;
 use crate::types::*;
 use crate::helpers::multiply_stake;
 use crate::logger;
 use crate::fungible_token::{ fungible_token_transfer };
+use crate::request_index::DataRequestState;
+use crate::oracle_config::{ BondEscalation, ArbitrationCouncil, ArbitrationFallback };
+use crate::requestor_handler::{ OutcomeDelivery, OutcomeRecipient };
+use crate::target_contract_handler::TargetContract;
 
 pub const PERCENTAGE_DIVISOR: u16 = 10_000;
+const MAX_BATCH_CLAIMS: usize = 20;
 pub const FINALIZATION_GAS: u64 = 250_000_000_000_000;
+const GAS_ON_SWEPT: u64 = 5_000_000_000_000;
 
 #[ext_contract]
 trait ExtSelf {
     fn dr_proceed_finalization(request_id: U64, sender: AccountId);
+    fn on_swept(request_id: U64, payment_amount: U128, stake_amount: U128);
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
@@ -32,6 +39,43 @@ pub enum DataRequestDataType {
     String,
 }
 
+/**
+ * @notice Band in which numeric answers are considered equal, either an absolute difference or a percentage denominated in `PERCENTAGE_DIVISOR`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub enum NumberTolerance {
+    Absolute(U128),
+    Relative(u16),
+}
+
+impl NumberTolerance {
+    // @returns the absolute width of the band around `value`, at least 1
+    pub fn width_for(&self, value: i128) -> u128 {
+        let width = match self {
+            NumberTolerance::Absolute(width) => u128::from(*width),
+            NumberTolerance::Relative(percentage) => helpers::calc_product(helpers::distance(value, 0), *percentage as u128, PERCENTAGE_DIVISOR as u128)
+        };
+        std::cmp::max(width, 1)
+    }
+
+    // @returns whether both outcomes are numbers inside each other's band, non numeric outcomes have to be equal
+    pub fn contains(&self, a: &Outcome, b: &Outcome) -> bool {
+        match (a.number_value(), b.number_value()) {
+            (Some(a_value), Some(b_value)) => {
+                let reference = if helpers::distance(a_value, 0) > helpers::distance(b_value, 0) { a_value } else { b_value };
+                helpers::distance(a_value, b_value) <= self.width_for(reference)
+            },
+            _ => a == b
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ArbitrationVote {
+    pub member: AccountId,
+    pub outcome: Outcome,
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct DataRequest {
     pub id: u64,
@@ -48,16 +92,43 @@ pub struct DataRequest {
     pub final_arbitrator_triggered: bool,
     pub tags: Option<Vec<String>>,
     pub data_type: DataRequestDataType,
+    pub reveal_period: Option<Duration>, // reveal period for commit-reveal requests, `None` for plain staking
+    pub tolerance: Option<NumberTolerance>, // numeric answers inside this band are bonded together
+    pub cancelled: bool,
+    pub settlement_time: Option<Timestamp>, // staking is blocked until this time
+    pub created_at: Timestamp,
+    pub expiry_fallback: Option<Outcome>, // outcome used when the request expires without a bonded outcome
+    pub expired: bool,
+    pub arbitration_votes: Vec<ArbitrationVote>, // votes of the arbitration council, if the request's config has one
+    pub final_arbitrator_triggered_at: Option<Timestamp>,
+    pub arbitration_fallback_used: bool, // finalized with the `arbitration_fallback` because the final arbitrator timed out
+    pub outcome_delivery: Option<OutcomeDelivery>, // `None` until the outcome is sent to the requestor
+    pub undelivered_recipients: Vec<OutcomeRecipient>, // recipients a retry sends the outcome to
+    pub target_contract: Option<TargetContract>, // receives the outcome instead of, or next to, the requestor
+    pub finalized_at: Option<Timestamp>,
+    pub participants: UnorderedSet<AccountId>, // accounts that staked or committed, used to prune the windows when archiving
+    pub claimed_correct_stake: Balance, // bonded stake on the final outcome that's been claimed
+    pub archived: bool, // the resolution windows are (being) deleted, only the outcome is kept
+    pub payment_balance: Balance, // payment tokens (validity bond and fee) held for this request
+    pub stake_balance: Balance, // stake tokens held for this request
+    pub swept: bool, // leftover funds are moved to the treasury, claims are closed
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct DataRequestConfig {
-    default_challenge_window_duration: Duration,
-    final_arbitrator_invoke_amount: Balance,
-    final_arbitrator: AccountId,
-    validity_bond: Balance,
+    pub(crate) default_challenge_window_duration: Duration,
+    pub(crate) final_arbitrator_invoke_amount: Balance,
+    pub(crate) final_arbitrator: AccountId,
+    pub(crate) validity_bond: Balance,
     pub paid_fee: Balance,
     pub stake_multiplier: Option<u16>,
+    pub slash_unrevealed_stake: bool,
+    pub(crate) request_expiry_duration: Duration,
+    pub bond_escalation: BondEscalation,
+    pub arbitration_council: Option<ArbitrationCouncil>,
+    pub(crate) arbitration_timeout: Duration,
+    pub(crate) arbitration_fallback: ArbitrationFallback,
+    pub(crate) claim_window_duration: Duration,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -76,6 +147,27 @@ pub struct DataRequestSummary {
     pub final_arbitrator_triggered: bool,
     pub tags: Option<Vec<String>>,
     pub data_type: DataRequestDataType,
+    pub reveal_period: Option<U64>,
+    pub tolerance: Option<NumberTolerance>,
+    pub cancelled: bool,
+    pub settlement_time: Option<WrappedTimestamp>,
+    pub state: DataRequestState,
+    pub created_at: WrappedTimestamp,
+    pub expiry_fallback: Option<Outcome>,
+    pub expired: bool,
+    pub arbitration_votes: Vec<ArbitrationVote>,
+    pub final_arbitrator_triggered_at: Option<WrappedTimestamp>,
+    pub arbitration_fallback_used: bool,
+    pub outcome_delivery: Option<OutcomeDelivery>,
+    pub undelivered_recipients: Vec<OutcomeRecipient>,
+    pub target_contract: Option<TargetContract>,
+    pub finalized_at: Option<WrappedTimestamp>,
+    pub claimed_correct_stake: WrappedBalance,
+    pub archived: bool,
+    pub claim_deadline: Option<WrappedTimestamp>,
+    pub payment_balance: WrappedBalance,
+    pub stake_balance: WrappedBalance,
+    pub swept: bool,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -83,17 +175,33 @@ pub struct DataRequestConfigSummary {
     pub validity_bond: WrappedBalance,
     pub paid_fee: WrappedBalance,
     pub stake_multiplier: Option<u16>,
+    pub slash_unrevealed_stake: bool,
+    pub request_expiry_duration: WrappedTimestamp,
+    pub bond_escalation: BondEscalation,
+    pub arbitration_council: Option<ArbitrationCouncil>,
+    pub arbitration_timeout: WrappedTimestamp,
+    pub arbitration_fallback: ArbitrationFallback,
+    pub claim_window_duration: WrappedTimestamp,
 }
 
 trait DataRequestChange {
     fn new(requestor: Requestor, id: u64, global_config_id: u64, global_config: &oracle_config::OracleConfig, paid_fee: Balance, request_data: NewDataRequestArgs) -> Self;
     fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance;
+    fn commit(&mut self, sender: AccountId, commitment: String, amount: Balance);
+    fn reveal(&mut self, sender: AccountId, round: u16, outcome: Outcome, salt: String) -> Balance;
+    fn store_current_window(&mut self, window: &ResolutionWindow);
+    fn open_next_window_if_bonded(&mut self, window: &ResolutionWindow);
     fn unstake(&mut self, sender: AccountId, round: u16, outcome: Outcome, amount: Balance) -> Balance;
     fn finalize(&mut self);
     fn invoke_final_arbitrator(&mut self, bond_size: Balance) -> bool;
     fn finalize_final_arbitrator(&mut self, outcome: Outcome);
+    fn vote_final_arbitrator(&mut self, member: AccountId, outcome: Outcome) -> bool;
     fn claim(&mut self, account_id: String) -> ClaimRes;
-    fn return_validity_bond(&self, token: AccountId) -> PromiseOrValue<bool>;
+    fn return_validity_bond(&mut self, token: AccountId) -> PromiseOrValue<bool>;
+    fn cancel(&mut self, token: AccountId) -> Promise;
+    fn expire(&mut self, token: AccountId) -> Promise;
+    fn archive(&mut self, limit: u64) -> Vec<(AccountId, StorageUsage)>;
+    fn sweep(&mut self, treasury: AccountId, payment_token: AccountId, stake_token: AccountId) -> Option<Promise>;
 }
 
 impl DataRequestChange for DataRequest {
@@ -122,7 +230,14 @@ impl DataRequestChange for DataRequest {
                 final_arbitrator: config.final_arbitrator.to_string(),
                 validity_bond: config.validity_bond.into(),
                 stake_multiplier: requestor.stake_multiplier,
-                paid_fee
+                paid_fee,
+                slash_unrevealed_stake: config.slash_unrevealed_stake,
+                request_expiry_duration: config.request_expiry_duration.into(),
+                bond_escalation: config.bond_escalation.clone(),
+                arbitration_council: config.arbitration_council.clone(),
+                arbitration_timeout: config.arbitration_timeout.into(),
+                arbitration_fallback: config.arbitration_fallback,
+                claim_window_duration: config.claim_window_duration.into(),
             },
             initial_challenge_period: request_data.challenge_period.into(),
             final_arbitrator_triggered: false,
@@ -130,6 +245,26 @@ impl DataRequestChange for DataRequest {
             tags: request_data.tags,
             data_type: request_data.data_type,
             creator: request_data.creator,
+            reveal_period: request_data.reveal_period.map(u64::from),
+            tolerance: request_data.tolerance,
+            cancelled: false,
+            settlement_time: request_data.settlement_time.map(u64::from),
+            created_at: env::block_timestamp(),
+            expiry_fallback: request_data.expiry_fallback,
+            expired: false,
+            arbitration_votes: Vec::new(),
+            final_arbitrator_triggered_at: None,
+            arbitration_fallback_used: false,
+            outcome_delivery: None,
+            undelivered_recipients: Vec::new(),
+            target_contract: request_data.target_contract,
+            finalized_at: None,
+            participants: UnorderedSet::new(format!("drp{}", id).as_bytes().to_vec()),
+            claimed_correct_stake: 0,
+            archived: false,
+            payment_balance: u128::from(config.validity_bond) + paid_fee,
+            stake_balance: 0,
+            swept: false,
         }
     }
 
@@ -139,40 +274,74 @@ impl DataRequestChange for DataRequest {
         outcome: Outcome,
         amount: Balance
     ) -> Balance {
-        let mut window = self.resolution_windows
-            .iter()
-            .last()
-            .unwrap_or_else( || {
-                ResolutionWindow::new(self.id, 0, self.calc_resolution_bond(), self.initial_challenge_period, env::block_timestamp())
-            });
+        let mut window = self.get_or_create_current_window();
+        self.participants.insert(&sender);
+        
+        let unspent = window.stake(sender, outcome, amount, self.tolerance.as_ref());
+        self.stake_balance += amount - unspent;
+
+        self.store_current_window(&window);
+        self.open_next_window_if_bonded(&window);
         
-        let unspent = window.stake(sender, outcome, amount);
+        unspent
+    }
+
+    fn commit(&mut self, sender: AccountId, commitment: String, amount: Balance) {
+        let mut window = self.get_or_create_current_window();
+        self.participants.insert(&sender);
+
+        window.commit(sender, commitment, amount);
+        self.stake_balance += amount;
+
+        self.store_current_window(&window);
+    }
+
+    // @returns amount of revealed tokens that didn't get staked
+    fn reveal(&mut self, sender: AccountId, round: u16, outcome: Outcome, salt: String) -> Balance {
+        let mut window = self.resolution_windows
+            .get(round as u64)
+            .expect("ERR_NO_RESOLUTION_WINDOW");
+
+        let unspent = window.reveal(sender, outcome, salt, self.tolerance.as_ref());
+        self.stake_balance = self.stake_balance.saturating_sub(unspent);
+        self.resolution_windows.replace(round as u64, &window);
+
+        // Only a reveal in the current window can open up a new dispute round
+        if round as u64 == self.resolution_windows.len() - 1 {
+            self.open_next_window_if_bonded(&window);
+        }
+
+        unspent
+    }
 
-        // If first window push it to vec, else replace updated window struct
+    // @notice If first window push it to vec, else replace updated window struct
+    fn store_current_window(&mut self, window: &ResolutionWindow) {
         if self.resolution_windows.len() == 0 {
-            self.resolution_windows.push(&window);
+            self.resolution_windows.push(window);
         } else {
             self.resolution_windows.replace(
                 self.resolution_windows.len() - 1, // Last window
-                &window
+                window
             );
         }
+    }
 
-        // Check if this stake is bonded for the current window and if the final arbitrator should be invoked.
-        // If the final arbitrator is invoked other stake won't come through.
+    // @notice Check if the current window is bonded and if the final arbitrator should be invoked.
+    // If the final arbitrator is invoked other stake won't come through.
+    fn open_next_window_if_bonded(&mut self, window: &ResolutionWindow) {
         if window.bonded_outcome.is_some() && !self.invoke_final_arbitrator(window.bond_size) {
+            let round = self.resolution_windows.len() as u16;
             self.resolution_windows.push(
                 &ResolutionWindow::new(
                     self.id,
-                    self.resolution_windows.len() as u16,
-                    window.bond_size,
+                    round,
+                    self.request_config.bond_escalation.next_bond(window.bond_size, round),
                     self.request_config.default_challenge_window_duration,
+                    self.reveal_period,
                     env::block_timestamp()
                 )
             );
         }
-        
-        unspent
     }
 
     // @returns amount of tokens that didn't get staked
@@ -181,34 +350,70 @@ impl DataRequestChange for DataRequest {
             .get(round as u64)
             .expect("ERR_NO_RESOLUTION_WINDOW");
 
-        window.unstake(sender, outcome, amount)
+        let unstaked = window.unstake(sender, outcome, amount);
+        self.stake_balance = self.stake_balance.saturating_sub(unstaked);
+        unstaked
     }
 
+    // @notice Finalizes with the last bonded outcome, or with the `arbitration_fallback` if the final arbitrator timed out
     fn finalize(&mut self) {
-        self.finalized_outcome = self.get_final_outcome();
+        self.finalized_at = Some(env::block_timestamp());
+        self.finalized_outcome = if self.final_arbitrator_triggered {
+            self.arbitration_fallback_used = true;
+            match self.request_config.arbitration_fallback {
+                ArbitrationFallback::LastBondedOutcome => self.get_final_outcome(),
+                ArbitrationFallback::Invalid => Some(Outcome::Invalid)
+            }
+        } else {
+            self.get_final_outcome()
+        };
     }
 
     // @returns wether final arbitrator was triggered
     fn invoke_final_arbitrator(&mut self, bond_size: Balance) -> bool {
         let should_invoke = bond_size >= self.request_config.final_arbitrator_invoke_amount;
-        if should_invoke { self.final_arbitrator_triggered = true }
+        if should_invoke && !self.final_arbitrator_triggered {
+            self.final_arbitrator_triggered = true;
+            self.final_arbitrator_triggered_at = Some(env::block_timestamp());
+        }
         self.final_arbitrator_triggered
     }
 
     fn finalize_final_arbitrator(&mut self, outcome: Outcome) {
+        self.finalized_at = Some(env::block_timestamp());
         self.finalized_outcome = Some(outcome);
     }
 
+    // @returns whether the vote made `outcome` reach the council's threshold
+    fn vote_final_arbitrator(&mut self, member: AccountId, outcome: Outcome) -> bool {
+        let council = self.request_config.arbitration_council.as_ref().unwrap();
+        assert!(
+            !self.arbitration_votes.iter().any(|vote| vote.member == member),
+            "{} already voted on `DataRequest` with id: {}",
+            member,
+            self.id
+        );
+
+        logger::log_arbitration_vote(self.id, &member, &outcome);
+        self.arbitration_votes.push(ArbitrationVote { member, outcome: outcome.clone() });
+
+        let votes_for_outcome = self.arbitration_votes.iter().filter(|vote| vote.outcome == outcome).count();
+        votes_for_outcome >= council.threshold as usize
+    }
+
     fn claim(&mut self, account_id: String) -> ClaimRes {
         // Metrics for calculating payout
         let mut total_correct_staked = 0;
         let mut total_incorrect_staked = 0;
         let mut user_correct_stake = 0;
+        let mut total_unrevealed = 0;
+        let mut user_unrevealed_stake = 0;
+        let mut user_unbonded_refund = 0;
 
         // For any round after the resolution round handle generically
         for round in 0..self.resolution_windows.len() {
             let mut window = self.resolution_windows.get(round).unwrap();
-            let stake_state: WindowStakeResult = window.claim_for(account_id.to_string(), self.finalized_outcome.as_ref().unwrap());
+            let stake_state: WindowStakeResult = window.claim_for(account_id.to_string(), self.finalized_outcome.as_ref().unwrap(), self.tolerance.as_ref());
             match stake_state {
                 WindowStakeResult::Correct(correctly_staked) => {
                     total_correct_staked += correctly_staked.bonded_stake;
@@ -220,9 +425,24 @@ impl DataRequestChange for DataRequest {
                 WindowStakeResult::NoResult => ()
             }
 
+            // Stake on outcomes that never filled the bond is returned
+            user_unbonded_refund += window.claim_unbonded_for(&account_id);
+
+            total_unrevealed += window.total_unrevealed();
+            user_unrevealed_stake += window.claim_unrevealed_for(&account_id);
+
             self.resolution_windows.replace(round as u64, &window);
         };
 
+        // Stake that was committed but never revealed is either shared with the correct stakers or returned to its owner,
+        // without correct stake there's nobody to share it with so it's always returned
+        let user_unrevealed_refund = if self.request_config.slash_unrevealed_stake && total_correct_staked > 0 {
+            total_incorrect_staked += total_unrevealed;
+            0
+        } else {
+            user_unrevealed_stake
+        };
+
         let stake_profit = match total_correct_staked {
             0 => 0,
             _ => helpers::calc_product(user_correct_stake, total_incorrect_staked, total_correct_staked)
@@ -234,39 +454,178 @@ impl DataRequestChange for DataRequest {
             _ => helpers::calc_product(user_correct_stake, self.request_config.paid_fee, total_correct_staked)
         };
 
-        logger::log_claim(&account_id, self.id, total_correct_staked, total_incorrect_staked, user_correct_stake, stake_profit, fee_profit);
+        self.claimed_correct_stake += user_correct_stake;
+        let refunded_stake = user_unbonded_refund + user_unrevealed_refund;
+        // Rounding can leave some dust, that's swept to the treasury after the claim deadline
+        self.payment_balance = self.payment_balance.saturating_sub(fee_profit);
+        self.stake_balance = self.stake_balance.saturating_sub(user_correct_stake + stake_profit + refunded_stake);
+        logger::log_claim(&account_id, self.id, total_correct_staked, total_incorrect_staked, user_correct_stake, stake_profit, fee_profit, refunded_stake);
 
         ClaimRes {
             payment_token_payout: fee_profit,
-            stake_token_payout: user_correct_stake + stake_profit
+            stake_token_payout: user_correct_stake + stake_profit + refunded_stake
         }
     }
 
     // @notice Return what's left of validity_bond to requestor
-    fn return_validity_bond(&self, token: AccountId) -> PromiseOrValue<bool> {
+    fn return_validity_bond(&mut self, token: AccountId) -> PromiseOrValue<bool> {
         match self.finalized_outcome.as_ref().unwrap() {
             Outcome::Answer(_) => {
+                self.payment_balance = self.payment_balance.saturating_sub(self.request_config.validity_bond);
                 PromiseOrValue::Promise(fungible_token_transfer(token, self.creator.clone(), self.request_config.validity_bond))
             },
             Outcome::Invalid => PromiseOrValue::Value(false)
 
         }
     }
+
+    // @notice Marks the request as cancelled and returns the validity bond and fee to the creator
+    fn cancel(&mut self, token: AccountId) -> Promise {
+        self.cancelled = true;
+        self.payment_balance = 0;
+        fungible_token_transfer(token, self.creator.clone(), self.request_config.validity_bond + self.request_config.paid_fee)
+    }
+
+    /**
+     * @notice Finalizes an unanswered request with the fallback outcome or `Outcome::Invalid`. Nobody did any resolution work
+     * so the validity bond and fee are returned to the creator. Stake in round 0 never filled the bond, so its stakers get it back
+     * through `dr_claim` like any other unbonded stake
+     */
+    fn expire(&mut self, token: AccountId) -> Promise {
+        self.expired = true;
+        self.finalized_at = Some(env::block_timestamp());
+        self.finalized_outcome = Some(self.expiry_fallback.clone().unwrap_or(Outcome::Invalid));
+        self.payment_balance = 0;
+        fungible_token_transfer(token, self.creator.clone(), self.request_config.validity_bond + self.request_config.paid_fee)
+    }
+
+    /**
+     * @notice Deletes the stake entries of up to `limit` participants, once every participant is pruned the remaining `limit`
+     * is spent on the outcomes of the resolution windows. The `DataRequest` itself is kept with its outcome and claims are closed
+     * @returns the storage freed for each pruned participant
+     */
+    fn archive(&mut self, limit: u64) -> Vec<(AccountId, StorageUsage)> {
+        self.archived = true;
+
+        let participants: Vec<AccountId> = self.participants.iter().take(limit as usize).collect();
+        let mut freed_storage = Vec::with_capacity(participants.len());
+        for account_id in &participants {
+            let initial_storage = env::storage_usage();
+            for mut window in self.resolution_windows.iter() {
+                window.prune_participant(account_id);
+            }
+            self.participants.remove(account_id);
+            freed_storage.push((account_id.to_string(), initial_storage.saturating_sub(env::storage_usage())));
+        }
+
+        let mut remaining = limit - participants.len() as u64;
+        while remaining > 0 && self.resolution_windows.len() > 0 {
+            let index = self.resolution_windows.len() - 1;
+            let mut window = self.resolution_windows.get(index).unwrap();
+            remaining -= window.prune_outcomes(remaining, self.tolerance.as_ref());
+            if window.staked_outcomes.len() == 0 {
+                self.resolution_windows.pop();
+            } else {
+                self.resolution_windows.replace(index, &window);
+            }
+        }
+
+        freed_storage
+    }
+
+    // @notice Moves the funds nobody claimed to the treasury, `on_swept` restores the funds of a failed transfer
+    fn sweep(&mut self, treasury: AccountId, payment_token: AccountId, stake_token: AccountId) -> Option<Promise> {
+        logger::log_sweep(self.id, &treasury, self.payment_balance, self.stake_balance);
+
+        let payment_amount = self.payment_balance;
+        let stake_amount = self.stake_balance;
+        let payment_transfer = match payment_amount {
+            0 => None,
+            amount => Some(fungible_token_transfer(payment_token, treasury.to_string(), amount))
+        };
+        let stake_transfer = match stake_amount {
+            0 => None,
+            amount => Some(fungible_token_transfer(stake_token, treasury, amount))
+        };
+
+        self.payment_balance = 0;
+        self.stake_balance = 0;
+        self.swept = true;
+
+        let transfers = match (payment_transfer, stake_transfer) {
+            (Some(payment_transfer), Some(stake_transfer)) => Some(payment_transfer.and(stake_transfer)),
+            (payment_transfer, stake_transfer) => payment_transfer.or(stake_transfer)
+        };
+
+        transfers.map(|transfers| transfers.then(ext_self::on_swept(
+            U64(self.id),
+            U128(payment_amount),
+            U128(stake_amount),
+            &env::current_account_id(),
+            0,
+            GAS_ON_SWEPT
+        )))
+    }
+}
+
+impl DataRequest {
+    // @returns the lifecycle state as it's stored in the state index, this doesn't depend on the block time
+    pub fn stored_state(&self) -> DataRequestState {
+        if self.finalized_outcome.is_some() {
+            DataRequestState::Finalized
+        } else if self.cancelled {
+            DataRequestState::Cancelled
+        } else if self.final_arbitrator_triggered {
+            DataRequestState::ArbitratorTriggered
+        } else if self.resolution_windows.len() == 0 {
+            DataRequestState::AwaitingFirstStake
+        } else {
+            DataRequestState::InDispute
+        }
+    }
+
+    // @returns the lifecycle state at the current block time
+    pub fn state(&self) -> DataRequestState {
+        let stored_state = self.stored_state();
+        if stored_state == DataRequestState::InDispute && self.resolution_windows.len() >= 2 {
+            let last_window = self.resolution_windows.get(self.resolution_windows.len() - 1).unwrap();
+            if env::block_timestamp() >= last_window.end_time {
+                return DataRequestState::Finalizable;
+            }
+        }
+        stored_state
+    }
 }
 
 trait DataRequestView {
     fn assert_valid_outcome(&self, outcome: &Outcome);
     fn assert_valid_outcome_type(&self, outcome: &Outcome);
-    fn assert_can_stake_on_outcome(&self, outcome: &Outcome);
+    fn assert_can_stake_on_outcome(&self, round: u64, outcome: &Outcome);
+    fn assert_commit_reveal(&self);
+    fn assert_not_commit_reveal(&self);
     fn assert_not_finalized(&self);
+    fn assert_not_cancelled(&self);
+    fn assert_settled(&self);
+    fn assert_can_expire(&self);
+    fn expires_at(&self) -> Timestamp;
+    fn assert_can_cancel(&self, account_id: &AccountId);
     fn assert_finalized(&self);
+    fn assert_not_archived(&self);
+    fn assert_not_swept(&self);
+    fn assert_can_archive(&self, claim_pauses: &pause::ClaimPauses);
+    fn archive_completed(&self) -> bool;
+    fn assert_can_sweep(&self, claim_pauses: &pause::ClaimPauses);
+    fn claim_deadline(&self, claim_pauses: &pause::ClaimPauses) -> Option<Timestamp>;
     fn assert_can_finalize(&self);
+    fn arbitration_timed_out(&self) -> bool;
     fn assert_final_arbitrator(&self);
+    fn assert_arbitration_council_member(&self);
     fn assert_final_arbitrator_invoked(&self);
     fn assert_final_arbitrator_not_invoked(&self);
     fn get_final_outcome(&self) -> Option<Outcome>;
     fn calc_resolution_bond(&self) -> Balance;
-    fn summarize_dr(&self) -> DataRequestSummary;
+    fn get_or_create_current_window(&self) -> ResolutionWindow;
+    fn summarize_dr(&self, claim_pauses: &pause::ClaimPauses) -> DataRequestSummary;
 }
 
 impl DataRequestView for DataRequest {
@@ -305,29 +664,147 @@ impl DataRequestView for DataRequest {
         }
     }
 
-    fn assert_can_stake_on_outcome(&self, outcome: &Outcome) {
-        if self.resolution_windows.len() > 1 {
-            let last_window = self.resolution_windows.get(self.resolution_windows.len() - 2).unwrap();
-            assert_ne!(&last_window.bonded_outcome.unwrap(), outcome, "Outcome is incompatible for this round");
+    // @notice Outcomes can't be staked on if they were bonded, or are inside the tolerance band of what was bonded, in the round before `round`
+    fn assert_can_stake_on_outcome(&self, round: u64, outcome: &Outcome) {
+        if round > 0 {
+            let prev_window = self.resolution_windows.get(round - 1).unwrap();
+            let prev_bonded_outcome = prev_window.bonded_outcome.unwrap();
+            match &self.tolerance {
+                Some(tolerance) => assert!(!tolerance.contains(&prev_bonded_outcome, outcome), "Outcome is incompatible for this round"),
+                None => assert_ne!(&prev_bonded_outcome, outcome, "Outcome is incompatible for this round")
+            }
         }
     }
 
+    fn assert_commit_reveal(&self) {
+        assert!(self.reveal_period.is_some(), "`DataRequest` with id: {} does not use commit-reveal staking", self.id);
+    }
+
+    fn assert_not_commit_reveal(&self) {
+        assert!(self.reveal_period.is_none(), "`DataRequest` with id: {} requires commit-reveal staking", self.id);
+    }
+
     fn assert_not_finalized(&self) {
         assert!(self.finalized_outcome.is_none(), "Can't stake in finalized DataRequest");
     }
 
+    fn assert_not_cancelled(&self) {
+        assert!(!self.cancelled, "`DataRequest` with id: {} is cancelled", self.id);
+    }
+
+    fn assert_settled(&self) {
+        if let Some(settlement_time) = self.settlement_time {
+            assert!(env::block_timestamp() >= settlement_time, "Can't stake on `DataRequest` with id: {} before settlement time {}", self.id, settlement_time);
+        }
+    }
+
+    // @notice Requests can only expire when no outcome was ever bonded
+    fn assert_can_expire(&self) {
+        self.assert_not_cancelled();
+        self.assert_not_finalized();
+        self.assert_final_arbitrator_not_invoked();
+        assert!(
+            self.resolution_windows.iter().all(|window| window.bonded_outcome.is_none()),
+            "`DataRequest` with id: {} has a bonded outcome and can't expire",
+            self.id
+        );
+        assert!(env::block_timestamp() >= self.expires_at(), "`DataRequest` with id: {} can't expire before {}", self.id, self.expires_at());
+    }
+
+    // @notice The expiry window starts at creation, or at the settlement time if the request has one
+    fn expires_at(&self) -> Timestamp {
+        let expiry_start = std::cmp::max(self.created_at, self.settlement_time.unwrap_or(0));
+        expiry_start + self.request_config.request_expiry_duration
+    }
+
+    // @notice Only the creator or requestor can cancel, and only before anyone staked
+    fn assert_can_cancel(&self, account_id: &AccountId) {
+        assert!(
+            account_id == &self.creator || account_id == &self.requestor.account_id,
+            "Only the creator or requestor can cancel `DataRequest` with id: {}",
+            self.id
+        );
+        self.assert_not_cancelled();
+        self.assert_not_finalized();
+        assert!(self.resolution_windows.len() == 0, "`DataRequest` with id: {} already received stake and can't be cancelled", self.id);
+    }
+
     fn assert_finalized(&self) {
         assert!(self.finalized_outcome.is_some(), "DataRequest is not finalized");
     }
 
+    fn assert_not_archived(&self) {
+        assert!(!self.archived, "`DataRequest` with id: {} is archived", self.id);
+    }
+
+    fn assert_not_swept(&self) {
+        assert!(!self.swept, "`DataRequest` with id: {} is swept, its claim window has closed", self.id);
+    }
+
+    fn assert_can_sweep(&self, claim_pauses: &pause::ClaimPauses) {
+        self.assert_finalized();
+        self.assert_not_swept();
+        let claim_deadline = self.claim_deadline(claim_pauses).unwrap();
+        assert!(
+            env::block_timestamp() >= claim_deadline,
+            "`DataRequest` with id: {} can't be swept before its claim deadline {}",
+            self.id,
+            claim_deadline
+        );
+    }
+
+    // @returns the end of the claim window extended by the time claiming was paused, `None` if the request isn't finalized
+    fn claim_deadline(&self, claim_pauses: &pause::ClaimPauses) -> Option<Timestamp> {
+        self.finalized_at.map(|finalized_at| claim_pauses.claim_deadline(finalized_at, self.request_config.claim_window_duration))
+    }
+
+    // @notice an archive that's in progress can always be continued, its first call already closed the claims
+    fn assert_can_archive(&self, claim_pauses: &pause::ClaimPauses) {
+        self.assert_finalized();
+        if self.archived {
+            assert!(!self.archive_completed(), "`DataRequest` with id: {} is archived", self.id);
+        } else {
+            let claim_deadline = self.claim_deadline(claim_pauses).unwrap();
+            // correct stake, unbonded stake and unrevealed commitments are all refunded from the resolution windows
+            assert!(
+                self.stake_balance == 0 || env::block_timestamp() >= claim_deadline,
+                "`DataRequest` with id: {} has unclaimed stake and can't be archived before {}",
+                self.id,
+                claim_deadline
+            );
+        }
+    }
+
+    fn archive_completed(&self) -> bool {
+        self.participants.len() == 0 && self.resolution_windows.len() == 0
+    }
+
     fn assert_can_finalize(&self) {
         let window = self.resolution_windows.get(self.resolution_windows.len() - 1).unwrap();
-        assert!(!self.final_arbitrator_triggered, "Can only be finalized by final arbitrator: {}", self.request_config.final_arbitrator);
-        assert!(env::block_timestamp() >= window.end_time, "Error can only be finalized after final dispute round has timed out");
+        if self.final_arbitrator_triggered {
+            assert!(self.arbitration_timed_out(), "Can only be finalized by final arbitrator: {}", self.request_config.final_arbitrator);
+        } else {
+            assert!(env::block_timestamp() >= window.end_time, "Error can only be finalized after final dispute round has timed out");
+        }
         self.assert_not_finalized();
     }
 
+    fn arbitration_timed_out(&self) -> bool {
+        match self.final_arbitrator_triggered_at {
+            Some(triggered_at) => {
+                self.request_config.arbitration_timeout > 0 &&
+                env::block_timestamp() >= triggered_at + self.request_config.arbitration_timeout
+            },
+            None => false
+        }
+    }
+
     fn assert_final_arbitrator(&self) {
+        assert!(
+            self.request_config.arbitration_council.is_none(),
+            "`DataRequest` with id: {} is arbitrated by a council, members should vote through `dr_final_arbitrator_vote`",
+            self.id
+        );
         assert_eq!(
             self.request_config.final_arbitrator,
             env::predecessor_account_id(),
@@ -336,6 +813,16 @@ impl DataRequestView for DataRequest {
         );
     }
 
+    fn assert_arbitration_council_member(&self) {
+        let council = self.request_config.arbitration_council.as_ref().expect("`DataRequest` has no arbitration council");
+        assert!(
+            council.is_member(&env::predecessor_account_id()),
+            "{} is not a member of the arbitration council of `DataRequest` with id: {}",
+            env::predecessor_account_id(),
+            self.id
+        );
+    }
+
     fn assert_final_arbitrator_invoked(&self) {
         assert!(
             self.final_arbitrator_triggered,
@@ -352,10 +839,13 @@ impl DataRequestView for DataRequest {
         );
     }
 
+    // @returns the outcome of the last bonded window, that's the window before the last one unless the final arbitrator was triggered
     fn get_final_outcome(&self) -> Option<Outcome> {
-        assert!(self.resolution_windows.iter().count() >= 2, "No bonded outcome found or final arbitrator triggered after first round");
-        let last_bonded_window_i = self.resolution_windows.len() - 2; // Last window after end_time never has a bonded outcome
-        let last_bonded_window = self.resolution_windows.get(last_bonded_window_i).unwrap();
+        let last_bonded_window = self.resolution_windows
+            .iter()
+            .rev()
+            .find(|window| window.bonded_outcome.is_some())
+            .expect("No bonded outcome found");
         last_bonded_window.bonded_outcome
     }
 
@@ -376,10 +866,20 @@ impl DataRequestView for DataRequest {
         multiply_stake(base_bond, self.request_config.stake_multiplier)
     }
 
+    fn get_or_create_current_window(&self) -> ResolutionWindow {
+        self.resolution_windows
+            .iter()
+            .last()
+            .unwrap_or_else( || {
+                let bond_size = self.request_config.bond_escalation.next_bond(self.calc_resolution_bond(), 0);
+                ResolutionWindow::new(self.id, 0, bond_size, self.initial_challenge_period, self.reveal_period, env::block_timestamp())
+            })
+    }
+
     /**
      * @notice Transforms a data request struct into another struct with Serde serialization
      */
-    fn summarize_dr(&self) -> DataRequestSummary {
+    fn summarize_dr(&self, claim_pauses: &pause::ClaimPauses) -> DataRequestSummary {
         // format resolution windows inside this data request
         let mut resolution_windows = Vec::new();
         for i in self.resolution_windows.iter() {
@@ -389,6 +889,9 @@ impl DataRequestView for DataRequest {
                 end_time: U64(i.end_time),
                 bond_size: U128(i.bond_size),
                 bonded_outcome: i.bonded_outcome,
+                commit_end_time: i.commit_end_time.map(U64),
+                total_committed: U128(i.total_committed),
+                total_revealed: U128(i.total_revealed),
             };
             resolution_windows.push(rw);
         }
@@ -408,15 +911,109 @@ impl DataRequestView for DataRequest {
             final_arbitrator_triggered: self.final_arbitrator_triggered,
             tags: self.tags.clone(),
             data_type: self.data_type.clone(),
+            reveal_period: self.reveal_period.map(U64),
+            tolerance: self.tolerance.clone(),
+            cancelled: self.cancelled,
+            settlement_time: self.settlement_time.map(U64),
+            state: self.state(),
+            created_at: U64(self.created_at),
+            expiry_fallback: self.expiry_fallback.clone(),
+            expired: self.expired,
+            arbitration_votes: self.arbitration_votes.clone(),
+            final_arbitrator_triggered_at: self.final_arbitrator_triggered_at.map(U64),
+            arbitration_fallback_used: self.arbitration_fallback_used,
+            outcome_delivery: self.outcome_delivery,
+            undelivered_recipients: self.undelivered_recipients.clone(),
+            target_contract: self.target_contract.clone(),
+            finalized_at: self.finalized_at.map(U64),
+            claimed_correct_stake: U128(self.claimed_correct_stake),
+            archived: self.archived,
+            claim_deadline: self.claim_deadline(claim_pauses).map(U64),
+            payment_balance: U128(self.payment_balance),
+            stake_balance: U128(self.stake_balance),
+            swept: self.swept,
             request_config: DataRequestConfigSummary {
                 validity_bond: U128(self.request_config.validity_bond),
                 paid_fee: U128(self.request_config.paid_fee),
                 stake_multiplier: self.request_config.stake_multiplier,
+                slash_unrevealed_stake: self.request_config.slash_unrevealed_stake,
+                request_expiry_duration: U64(self.request_config.request_expiry_duration),
+                bond_escalation: self.request_config.bond_escalation.clone(),
+                arbitration_council: self.request_config.arbitration_council.clone(),
+                arbitration_timeout: U64(self.request_config.arbitration_timeout),
+                arbitration_fallback: self.request_config.arbitration_fallback,
+                claim_window_duration: U64(self.request_config.claim_window_duration),
             }
         }
     }
 }
 
+impl Contract {
+    /**
+     * @notice Creates and stores a new `DataRequest`, the payload should already be validated
+     * @returns id of the new `DataRequest`
+     */
+    pub fn dr_create(
+        &mut self,
+        requestor: Requestor,
+        global_config_id: u64,
+        config: &oracle_config::OracleConfig,
+        paid_fee: Balance,
+        payload: NewDataRequestArgs
+    ) -> u64 {
+        assert!(self.legacy_data_requests.is_none(), "`DataRequest`s are still being migrated");
+        let dr = DataRequest::new(
+            requestor,
+            self.data_requests.len() as u64, // dr_id
+            global_config_id,
+            config,
+            paid_fee,
+            payload
+        );
+
+        if let Some(expiry_fallback) = &dr.expiry_fallback {
+            dr.assert_valid_outcome(expiry_fallback);
+            dr.assert_valid_outcome_type(expiry_fallback);
+        }
+
+        logger::log_new_data_request(&dr);
+
+        self.data_requests.push(&dr);
+        self.index_new_request(&dr);
+
+        dr.id
+    }
+
+    /**
+     * @notice Restores the funds of the sweep transfers that failed, the `DataRequest` can then be swept again
+     * @param results the results of the transfers, only non-zero amounts are transferred
+     * @returns whether every transfer succeeded
+     */
+    pub(crate) fn record_sweep(&mut self, request_id: U64, payment_amount: Balance, stake_amount: Balance, results: Vec<PromiseResult>) -> bool {
+        let mut results = results.into_iter();
+        let mut transferred = |amount: Balance| amount == 0 || match results.next() {
+            Some(PromiseResult::Successful(_)) => true,
+            _ => false
+        };
+        let payment_transferred = transferred(payment_amount);
+        let stake_transferred = transferred(stake_amount);
+
+        let mut dr = self.dr_get_expect(request_id);
+        if !payment_transferred {
+            dr.payment_balance += payment_amount;
+        }
+        if !stake_transferred {
+            dr.stake_balance += stake_amount;
+        }
+        let swept = payment_transferred && stake_transferred;
+        dr.swept = swept;
+        self.data_requests.replace(request_id.into(), &dr);
+
+        logger::log_update_data_request(&dr);
+        swept
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     pub fn dr_exists(&self, id: U64) -> bool {
@@ -425,11 +1022,13 @@ impl Contract {
 
     // Merge config and payload
     pub fn dr_new(&mut self, sender: AccountId, amount: Balance, payload: NewDataRequestArgs) -> Balance {
-        let config = self.get_config();
-        let validity_bond: u128 = config.validity_bond.into();
+        self.assert_not_paused(pause::Operation::NewRequest);
         self.assert_whitelisted(sender.to_string());
+        let requestor = self.whitelist.whitelist_get_expect(&sender);
+        let config = self.get_config().for_requestor(&requestor);
+        let validity_bond: u128 = config.validity_bond.into();
         self.assert_sender(&config.payment_token);
-        self.dr_validate(&payload);
+        self.dr_validate(&payload, &requestor);
         assert!(
             amount >= validity_bond,
             "Validity bond of {} not reached, received only {}",
@@ -439,48 +1038,123 @@ impl Contract {
 
         let paid_fee = amount - validity_bond;
         
-        let requestor = self.whitelist.whitelist_get_expect(&sender);
-        let dr = DataRequest::new(
+        self.dr_create(
             requestor,
-            self.data_requests.len() as u64, // dr_id
             self.configs.len() - 1, // dr's config id
             &config,
             paid_fee,
             payload
         );
 
-        logger::log_new_data_request(&dr);
-
-        self.data_requests.push(&dr);
-
         0
     }
 
     #[payable]
     pub fn dr_stake(&mut self, sender: AccountId, amount: Balance, payload: StakeDataRequestArgs) -> PromiseOrValue<WrappedBalance> {
+        self.assert_not_paused(pause::Operation::Stake);
         let mut dr = self.dr_get_expect(payload.id.into());
+        let prev_state = dr.stored_state();
         let config = self.configs.get(dr.global_config_id).unwrap();
         self.assert_sender(&config.stake_token);
+        dr.assert_not_cancelled();
+        dr.assert_settled();
         dr.assert_final_arbitrator_not_invoked();
-        dr.assert_can_stake_on_outcome(&payload.outcome);
+        dr.assert_not_commit_reveal();
+        dr.assert_can_stake_on_outcome(dr.resolution_windows.len().saturating_sub(1), &payload.outcome);
         dr.assert_valid_outcome(&payload.outcome);
         dr.assert_valid_outcome_type(&payload.outcome);
         dr.assert_not_finalized();
 
-        let unspent_stake = dr.stake(sender, payload.outcome, amount);
+        let round = dr.resolution_windows.len().saturating_sub(1) as u16;
+        let unspent_stake = dr.stake(sender.to_string(), payload.outcome.clone(), amount);
+        self.add_account_position(&sender, dr.id, round, &payload.outcome, amount - unspent_stake);
         logger::log_update_data_request(&dr);
         self.data_requests.replace(payload.id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
 
         PromiseOrValue::Value(U128(unspent_stake))
     }
 
+    /**
+     * @notice commits hidden stake to the current round of a commit-reveal `DataRequest`, the outcome is revealed later through `dr_reveal`
+     * @returns amount of tokens that didn't get committed
+     */
+    #[payable]
+    pub fn dr_commit(&mut self, sender: AccountId, amount: Balance, payload: CommitDataRequestArgs) -> PromiseOrValue<WrappedBalance> {
+        self.assert_not_paused(pause::Operation::Stake);
+        let mut dr = self.dr_get_expect(payload.id);
+        let prev_state = dr.stored_state();
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        self.assert_sender(&config.stake_token);
+        dr.assert_not_cancelled();
+        dr.assert_settled();
+        dr.assert_final_arbitrator_not_invoked();
+        dr.assert_commit_reveal();
+        dr.assert_not_finalized();
+        assert!(payload.commitment.len() == 64, "Commitment should be a hex encoded sha256 hash");
+
+        dr.commit(sender, payload.commitment, amount);
+        logger::log_update_data_request(&dr);
+        self.data_requests.replace(payload.id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /**
+     * @notice reveals the outcome behind a commitment, the revealed stake counts towards the round's bond
+     * @returns amount of tokens refunded because they didn't get staked
+     */
+    #[payable]
+    pub fn dr_reveal(&mut self, request_id: U64, round: u16, outcome: Outcome, salt: String) -> U128 {
+        self.assert_not_paused(pause::Operation::Stake);
+        let initial_storage = env::storage_usage();
+
+        let mut dr = self.dr_get_expect(request_id);
+        let prev_state = dr.stored_state();
+        dr.assert_commit_reveal();
+        dr.assert_not_cancelled();
+        dr.assert_not_finalized();
+        dr.assert_final_arbitrator_not_invoked();
+        dr.assert_can_stake_on_outcome(round as u64, &outcome);
+        dr.assert_valid_outcome(&outcome);
+        dr.assert_valid_outcome_type(&outcome);
+
+        let committed = dr.resolution_windows
+            .get(round as u64)
+            .and_then(|window| window.user_to_commitment.get(&env::predecessor_account_id()))
+            .map(|commitment| commitment.amount)
+            .unwrap_or(0);
+        let unspent = dr.reveal(env::predecessor_account_id(), round, outcome.clone(), salt);
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
+        self.add_account_position(&env::predecessor_account_id(), dr.id, round, &outcome, committed - unspent);
+
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+        logger::log_update_data_request(&dr);
+
+        if unspent > 0 {
+            fungible_token_transfer(config.stake_token, env::predecessor_account_id(), unspent);
+        }
+
+        U128(unspent)
+    }
+
+    pub fn dr_calc_commitment(&self, account_id: AccountId, outcome: Outcome, salt: String) -> String {
+        helpers::calc_commitment(&account_id, &outcome, &salt)
+    }
+
     #[payable]
     pub fn dr_unstake(&mut self, request_id: U64, resolution_round: u16, outcome: Outcome, amount: U128) {
+        self.assert_not_paused(pause::Operation::Unstake);
         let initial_storage = env::storage_usage();
 
         let mut dr = self.dr_get_expect(request_id.into());
-        let unstaked = dr.unstake(env::predecessor_account_id(), resolution_round, outcome, amount.into());
+        let unstaked = dr.unstake(env::predecessor_account_id(), resolution_round, outcome.clone(), amount.into());
         let config = self.configs.get(dr.global_config_id).unwrap();
+        self.data_requests.replace(request_id.into(), &dr);
+        self.remove_account_position(&env::predecessor_account_id(), dr.id, resolution_round, &outcome, unstaked);
 
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
         logger::log_update_data_request(&dr);
@@ -493,12 +1167,17 @@ impl Contract {
      */
     #[payable]
     pub fn dr_claim(&mut self, account_id: String, request_id: U64) -> Promise {
+        self.assert_not_paused(pause::Operation::Claim);
         let initial_storage = env::storage_usage();
 
         let mut dr = self.dr_get_expect(request_id.into());
         dr.assert_finalized();
+        dr.assert_not_archived();
+        dr.assert_not_swept();
         let stake_payout = dr.claim(account_id.to_string());
         let config = self.configs.get(dr.global_config_id).unwrap();
+        self.data_requests.replace(request_id.into(), &dr);
+        self.mark_account_positions_claimed(&account_id, dr.id);
 
         logger::log_update_data_request(&dr);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
@@ -524,42 +1203,219 @@ impl Contract {
         }
     }
 
-    pub fn dr_finalize(&mut self, request_id: U64) {
-        let mut dr = self.dr_get_expect(request_id.into());
-        dr.assert_can_finalize();
-        let final_outcome = dr.get_final_outcome();
-        
-        dr.requestor.set_outcome(request_id, final_outcome.unwrap(), dr.tags.clone(), false);
+    /**
+     * @notice cancels a `DataRequest` that hasn't received any stake yet, refunding the validity bond and fee to the creator
+     */
+    pub fn dr_cancel(&mut self, request_id: U64) -> Promise {
+        let mut dr = self.dr_get_expect(request_id);
+        let prev_state = dr.stored_state();
+        dr.assert_can_cancel(&env::predecessor_account_id());
 
         let config = self.configs.get(dr.global_config_id).unwrap();
-
-        dr.finalize();
-        dr.return_validity_bond(config.payment_token);
-
+        let refund = dr.cancel(config.payment_token);
         self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
 
         logger::log_update_data_request(&dr);
-    }
 
-    #[payable]
-    pub fn dr_final_arbitrator_finalize(&mut self, request_id: U64, outcome: Outcome) -> PromiseOrValue<bool> {
-        let initial_storage = env::storage_usage();
+        refund
+    }
 
+    /**
+     * @notice finalizes a `DataRequest` that never got a bonded outcome after its expiry window, can be called by anyone
+     */
+    pub fn dr_expire(&mut self, request_id: U64) -> Promise {
+        self.assert_not_paused(pause::Operation::Finalize);
         let mut dr = self.dr_get_expect(request_id);
-        dr.assert_not_finalized();
-        dr.assert_final_arbitrator();
-        dr.assert_valid_outcome(&outcome);
-        dr.assert_final_arbitrator_invoked();
-        dr.finalize_final_arbitrator(outcome.clone());
+        let prev_state = dr.stored_state();
+        dr.assert_can_expire();
 
         let config = self.configs.get(dr.global_config_id).unwrap();
-        dr.requestor.set_outcome(request_id, outcome, dr.tags.clone(), true);
+        let refund = dr.expire(config.payment_token);
+        dr.deliver_outcome(None);
         self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
+
+        logger::log_update_data_request(&dr);
+
+        refund
+    }
+
+    /**
+     * @notice claims for multiple `DataRequest`s at once, payouts are summed up per token so at most one transfer per token is made
+     */
+    #[payable]
+    pub fn dr_claim_many(&mut self, account_id: String, request_ids: Vec<U64>) -> Promise {
+        self.assert_not_paused(pause::Operation::Claim);
+        let initial_storage = env::storage_usage();
+        assert!(
+            !request_ids.is_empty() && request_ids.len() <= MAX_BATCH_CLAIMS,
+            "Can only claim between 1 and {} requests at once",
+            MAX_BATCH_CLAIMS
+        );
+
+        let mut payouts: Vec<(AccountId, Balance)> = Vec::new();
+        for request_id in request_ids {
+            let mut dr = self.dr_get_expect(request_id);
+            dr.assert_finalized();
+            dr.assert_not_archived();
+            dr.assert_not_swept();
+            let stake_payout = dr.claim(account_id.to_string());
+            let config = self.configs.get(dr.global_config_id).unwrap();
+            self.data_requests.replace(request_id.into(), &dr);
+            self.mark_account_positions_claimed(&account_id, dr.id);
+
+            logger::log_update_data_request(&dr);
+
+            add_payout(&mut payouts, config.stake_token, stake_payout.stake_token_payout);
+            add_payout(&mut payouts, config.payment_token, stake_payout.payment_token_payout);
+        }
+
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+
+        payouts
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0)
+            .fold(None, |prev_prom: Option<Promise>, (token, amount)| {
+                let transfer = fungible_token_transfer(token, account_id.to_string(), amount);
+                match prev_prom {
+                    Some(p) => Some(p.then(transfer)),
+                    None => Some(transfer)
+                }
+            })
+            .expect("can't claim 0")
+    }
+
+    /**
+     * @notice Deletes the resolution windows of a finalized `DataRequest` once all of its stake is claimed or refunded
+     * or the claim window has passed. The freed storage of each participant is credited to that participant's storage balance,
+     * the storage of the windows and outcomes is shared by the stakers and stays with the oracle
+     * @param limit the amount of participants and outcomes to prune in this call, call again until `true` is returned
+     * @returns whether the `DataRequest` is fully archived
+     */
+    pub fn dr_archive(&mut self, request_id: U64, limit: U64) -> bool {
+        self.assert_not_paused(pause::Operation::Claim);
+        let mut dr = self.dr_get_expect(request_id);
+        dr.assert_can_archive(&self.claim_pauses);
+        let freed_storage = dr.archive(limit.into());
+        self.data_requests.replace(request_id.into(), &dr);
+        for (account_id, storage) in freed_storage {
+            self.release_storage(&account_id, storage);
+        }
+
+        logger::log_update_data_request(&dr);
+        dr.archive_completed()
+    }
+
+    /**
+     * @notice Moves the payment and stake tokens of a `DataRequest` that are unclaimed after its claim deadline to the treasury
+     * @returns the transfers to the treasury, `None` if there was nothing left to sweep
+     */
+    pub fn dr_sweep(&mut self, request_id: U64) -> Option<Promise> {
+        self.assert_not_paused(pause::Operation::Claim);
+        let mut dr = self.dr_get_expect(request_id);
+        dr.assert_can_sweep(&self.claim_pauses);
+
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        let treasury = self.get_config().treasury;
+        let transfers = dr.sweep(treasury, config.payment_token, config.stake_token);
+        self.data_requests.replace(request_id.into(), &dr);
+
+        logger::log_update_data_request(&dr);
+        transfers
+    }
+
+    /**
+     * @notice called after the transfers of `dr_sweep` to restore the funds of a failed transfer
+     */
+    #[private]
+    pub fn on_swept(&mut self, request_id: U64, payment_amount: U128, stake_amount: U128) -> bool {
+        let results = (0..env::promise_results_count()).map(env::promise_result).collect();
+        self.record_sweep(request_id, payment_amount.into(), stake_amount.into(), results)
+    }
+
+    pub fn dr_finalize(&mut self, request_id: U64) {
+        self.assert_not_paused(pause::Operation::Finalize);
+        let mut dr = self.dr_get_expect(request_id.into());
+        let prev_state = dr.stored_state();
+        dr.assert_can_finalize();
+        dr.finalize();
+
+        dr.deliver_outcome(None);
+
+        let config = self.configs.get(dr.global_config_id).unwrap();
+
+        dr.return_validity_bond(config.payment_token);
+
+        self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
+
+        logger::log_update_data_request(&dr);
+    }
+
+    #[payable]
+    pub fn dr_final_arbitrator_finalize(&mut self, request_id: U64, outcome: Outcome) -> PromiseOrValue<bool> {
+        self.assert_not_paused(pause::Operation::Finalize);
+        let initial_storage = env::storage_usage();
+
+        let mut dr = self.dr_get_expect(request_id);
+        let prev_state = dr.stored_state();
+        dr.assert_not_finalized();
+        dr.assert_final_arbitrator();
+        dr.assert_valid_outcome(&outcome);
+        dr.assert_final_arbitrator_invoked();
+        dr.finalize_final_arbitrator(outcome);
+
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        dr.deliver_outcome(None);
+        let validity_bond_returned = dr.return_validity_bond(config.payment_token);
+        self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
+
+        logger::log_update_data_request(&dr);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+
+        validity_bond_returned
+    }
+
+    /**
+     * @notice votes on the outcome as a member of the arbitration council, the request is finalized once an outcome reaches the threshold
+     * @returns whether the validity bond was returned, `false` as long as the request is not finalized
+     */
+    #[payable]
+    pub fn dr_final_arbitrator_vote(&mut self, request_id: U64, outcome: Outcome) -> PromiseOrValue<bool> {
+        self.assert_not_paused(pause::Operation::Finalize);
+        let initial_storage = env::storage_usage();
+
+        let mut dr = self.dr_get_expect(request_id);
+        let prev_state = dr.stored_state();
+        dr.assert_not_finalized();
+        dr.assert_arbitration_council_member();
+        dr.assert_valid_outcome(&outcome);
+        dr.assert_valid_outcome_type(&outcome);
+        dr.assert_final_arbitrator_invoked();
+
+        let reached_threshold = dr.vote_final_arbitrator(env::predecessor_account_id(), outcome.clone());
+        let validity_bond_returned = if reached_threshold {
+            dr.finalize_final_arbitrator(outcome);
+            dr.deliver_outcome(None);
+            let config = self.configs.get(dr.global_config_id).unwrap();
+            dr.return_validity_bond(config.payment_token)
+        } else {
+            PromiseOrValue::Value(false)
+        };
+
+        self.data_requests.replace(request_id.into(), &dr);
+        self.index_request_state(prev_state, &dr);
 
         logger::log_update_data_request(&dr);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
 
-        dr.return_validity_bond(config.payment_token)
+        validity_bond_returned
+    }
+
+    pub fn get_arbitration_votes(&self, request_id: U64) -> Vec<ArbitrationVote> {
+        self.dr_get_expect(request_id).arbitration_votes
     }
 
     fn dr_get_expect(&self, id: U64) -> DataRequest {
@@ -570,7 +1426,7 @@ impl Contract {
         let dr = self.data_requests.get(id.into());
         match dr {
             None => None,
-            Some(d) => Some(d.summarize_dr())
+            Some(d) => Some(d.summarize_dr(&self.claim_pauses))
         }
     }
 
@@ -581,7 +1437,7 @@ impl Contract {
         let dr = self.data_requests.get(self.data_requests.len() - 1);
         match dr {
             None => None,
-            Some(d) => Some(d.summarize_dr())
+            Some(d) => Some(d.summarize_dr(&self.claim_pauses))
         }
     }
 
@@ -594,18 +1450,27 @@ impl Contract {
     pub fn get_requests(&self, from_index: U64, limit: U64) -> Vec<DataRequestSummary> {
         let i: u64 = from_index.into();
         (i..std::cmp::min(i + u64::from(limit), self.data_requests.len()))
-            .map(|index| self.data_requests.get(index).unwrap().summarize_dr())
+            .map(|index| self.data_requests.get(index).unwrap().summarize_dr(&self.claim_pauses))
             .collect()
     }
 }
 
+// @notice Adds `amount` to the payout of `token`
+fn add_payout(payouts: &mut Vec<(AccountId, Balance)>, token: AccountId, amount: Balance) {
+    match payouts.iter_mut().find(|(payout_token, _)| payout_token == &token) {
+        Some((_, total)) => *total += amount,
+        None => payouts.push((token, amount))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod mock_token_basic_tests {
     use near_sdk::{ MockedBlockchain };
-    use near_sdk::{ testing_env, VMContext };
-    use crate::requestor_handler::{Requestor};
-    use crate::data_request::AnswerType;
+    use near_sdk::{ testing_env, VMContext, PromiseResult };
+    use crate::requestor_handler::{ Requestor, RequestorOverrides };
+    use crate::data_request::{ AnswerType, AnswerNumberType };
+    use crate::request_index::{ RequestFilter, RequestPage };
     use super::*;
     use fee_config::FeeConfig;
 
@@ -642,7 +1507,9 @@ mod mock_token_basic_tests {
             interface_name: account.clone(),
             account_id: account.clone(),
             stake_multiplier: None,
-            code_base_url: None
+            code_base_url: None,
+            callback: None,
+            overrides: None
         }
     }
 
@@ -664,10 +1531,21 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(250),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
-                resolution_fee_percentage: 10_000,
+                resolution_fee_percentage: 5000, // 5%
             }
         }
     }
@@ -708,6 +1586,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -726,6 +1609,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -743,6 +1631,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -769,6 +1662,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -797,6 +1695,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -814,6 +1717,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -832,6 +1740,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -850,6 +1763,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -868,6 +1786,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -885,10 +1808,106 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
         assert_eq!(amount, 0);
     }
 
+    fn requestor_bounds() -> oracle_config::RequestorBounds {
+        oracle_config::RequestorBounds {
+            min_validity_bond: U128(50),
+            max_validity_bond: U128(1000),
+            min_challenge_window_duration: U64(500),
+            max_challenge_window_duration: U64(5000),
+            max_outcomes: 16,
+            min_final_arbitrator_invoke_amount: U128(100),
+            max_final_arbitrator_invoke_amount: U128(10000),
+        }
+    }
+
+    fn overridden_entry(account: AccountId) -> Requestor {
+        let mut requestor = registry_entry(account);
+        requestor.overrides = Some(RequestorOverrides {
+            validity_bond: Some(U128(500)),
+            min_initial_challenge_window_duration: Some(U64(2000)),
+            default_challenge_window_duration: Some(U64(2000)),
+            max_outcomes: None,
+            final_arbitrator_invoke_amount: Some(U128(5000)),
+        });
+        requestor
+    }
+
+    fn dr_new_challenge_period(contract: &mut Contract, amount: Balance, challenge_period: u64) -> Balance {
+        contract.dr_new(bob(), amount, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: None,
+            challenge_period: U64(challenge_period),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
+        })
+    }
+
+    #[test]
+    fn dr_new_requestor_overrides() {
+        testing_env!(get_context(token()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let whitelist = Some(vec![overridden_entry(bob())]);
+        let mut contract = Contract::new(whitelist, c);
+
+        dr_new_challenge_period(&mut contract, 600, 4000);
+        let dr = contract.dr_get_expect(U64(0));
+        assert_eq!(dr.request_config.validity_bond, 500);
+        assert_eq!(dr.request_config.paid_fee, 100);
+        assert_eq!(dr.request_config.default_challenge_window_duration, 2000);
+        assert_eq!(dr.request_config.final_arbitrator_invoke_amount, 5000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Validity bond of 500 not reached, received only 100")]
+    fn dr_new_requestor_overrides_validity_bond() {
+        testing_env!(get_context(token()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let whitelist = Some(vec![overridden_entry(bob())]);
+        let mut contract = Contract::new(whitelist, c);
+
+        dr_new_challenge_period(&mut contract, 100, 2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Challenge shorter than minimum challenge period of 2000")]
+    fn dr_new_requestor_overrides_challenge_period() {
+        testing_env!(get_context(token()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let whitelist = Some(vec![overridden_entry(bob())]);
+        let mut contract = Contract::new(whitelist, c);
+
+        dr_new_challenge_period(&mut contract, 500, 1500);
+    }
+
+    #[test]
+    fn dr_new_requestor_overrides_ignored_without_bounds() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![overridden_entry(bob())]);
+        let mut contract = Contract::new(whitelist, config());
+
+        dr_new_challenge_period(&mut contract, 100, 1500);
+        assert_eq!(contract.dr_get_expect(U64(0)).request_config.validity_bond, 100);
+    }
+
     fn dr_new(contract : &mut Contract) {
         contract.dr_new(bob(), 100, NewDataRequestArgs{
             sources: Vec::new(),
@@ -898,6 +1917,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
     }
 
@@ -982,6 +2006,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
 
         contract.dr_stake(alice(), 200, StakeDataRequestArgs{
@@ -1578,6 +2607,37 @@ mod mock_token_basic_tests {
         assert_eq!(sum_claim_res(d.claim(carol())), 0);
     }
 
+    #[test]
+    fn d_claim_refunds_unbonded_stake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = config();
+        config.final_arbitrator_invoke_amount = U128(1000);
+        let mut contract = Contract::new(whitelist, config);
+        dr_new(&mut contract);
+
+        // carol's stake on "b" in round 0 never fills the bond
+        contract.dr_stake(carol(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        contract.dr_stake(bob(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+        contract.dr_stake(carol(), 30, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        dr_finalize(&mut contract, data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+
+        let mut d = contract.data_requests.get(0).unwrap();
+        // carol: 50 refunded from round 0, 30 correct stake in round 1 receives 30/400 of the 200 incorrect stake
+        assert_eq!(sum_claim_res(d.claim(carol())), 50 + 30 + 15);
+        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+        assert_eq!(sum_claim_res(d.claim(bob())), 0);
+    }
+
     #[test]
     #[should_panic(expected = "Final arbitrator is invoked for `DataRequest` with id: 0")]
     fn dr_final_arb_invoked() {
@@ -1694,6 +2754,86 @@ mod mock_token_basic_tests {
         assert_eq!(request.finalized_outcome.unwrap(), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
     }
 
+    fn dr_new_council_arbitrated(contract: &mut Contract) {
+        dr_new(contract);
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+        // This round exceeds final arb limit, will be used as signal
+        contract.dr_stake(bob(), 400, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+    }
+
+    fn council_config() -> oracle_config::OracleConfig {
+        let mut config = config();
+        config.arbitration_council = Some(ArbitrationCouncil {
+            members: vec![alice(), bob(), carol()],
+            threshold: 2,
+        });
+        config
+    }
+
+    #[test]
+    fn dr_final_arb_council_quorum() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, council_config());
+        dr_new_council_arbitrated(&mut contract);
+
+        testing_env!(get_context(alice()));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+        testing_env!(get_context(bob()));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("a".to_string())));
+        assert!(contract.data_requests.get(0).unwrap().finalized_outcome.is_none());
+
+        testing_env!(get_context(carol()));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.finalized_outcome.unwrap(), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+        assert_eq!(contract.get_arbitration_votes(U64(0)).len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near already voted on `DataRequest` with id: 0")]
+    fn dr_final_arb_council_vote_twice() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, council_config());
+        dr_new_council_arbitrated(&mut contract);
+
+        testing_env!(get_context(alice()));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "dave.near is not a member of the arbitration council of `DataRequest` with id: 0")]
+    fn dr_final_arb_council_non_member() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, council_config());
+        dr_new_council_arbitrated(&mut contract);
+
+        testing_env!(get_context(dave()));
+        contract.dr_final_arbitrator_vote(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 is arbitrated by a council, members should vote through `dr_final_arbitrator_vote`")]
+    fn dr_final_arb_council_single_finalize() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, council_config());
+        dr_new_council_arbitrated(&mut contract);
+
+        testing_env!(get_context(alice()));
+        contract.dr_final_arbitrator_finalize(U64(0), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+    }
+
     #[test]
     fn dr_tvl_increases() {
         testing_env!(get_context(token()));
@@ -1716,6 +2856,8 @@ mod mock_token_basic_tests {
             account_id: bob(),
             stake_multiplier: None,
             code_base_url: None,
+            callback: None,
+            overrides: None,
         };
         let fixed_fee = 20; 
         let whitelist = Some(vec![bob_requestor, registry_entry(carol())]);
@@ -1731,6 +2873,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
         dr_finalize(&mut contract, data_request::Outcome::Answer(
             data_request::AnswerType::String("a".to_string())
@@ -1741,21 +2888,1296 @@ mod mock_token_basic_tests {
         assert_eq!(sum_claim_res(d.claim(alice())), 60);
     }
 
+    fn dr_new_commit_reveal(contract : &mut Contract) {
+        contract.dr_new(bob(), 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: Some(U64(1000)),
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
+        });
+    }
+
+    fn commit(contract: &mut Contract, account_id: AccountId, outcome: &Outcome, amount: Balance) {
+        contract.dr_commit(account_id.to_string(), amount, CommitDataRequestArgs{
+            id: U64(0),
+            commitment: helpers::calc_commitment(&account_id, outcome, "salt")
+        });
+    }
+
+    fn reveal_at(contract: &mut Contract, account_id: AccountId, outcome: Outcome, timestamp: u64) {
+        let mut ct : VMContext = get_context(account_id);
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.dr_reveal(U64(0), 0, outcome, "salt".to_string());
+    }
+
     #[test]
-    fn dr_get_methods() {
+    #[should_panic(expected = "`DataRequest` with id: 0 requires commit-reveal staking")]
+    fn dr_commit_reveal_no_plain_stake() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
-        dr_new(&mut contract);
-        dr_new(&mut contract);
-        dr_new(&mut contract);
-        
-        assert_eq!(contract.get_latest_request().unwrap().id, 2);
-        assert_eq!(contract.get_request_by_id(U64(1)).unwrap().id, 1);
+        dr_new_commit_reveal(&mut contract);
 
-        assert_eq!(contract.get_requests(U64(0), U64(1))[0].id, 0);
-        assert_eq!(contract.get_requests(U64(1), U64(1)).len(), 1);
-        assert_eq!(contract.get_requests(U64(1), U64(2)).len(), 2);
-        assert_eq!(contract.get_requests(U64(0), U64(3)).len(), 3);
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only reveal during the reveal phase of round 0")]
+    fn dr_commit_reveal_early_reveal() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_commit_reveal(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        commit(&mut contract, alice(), &outcome, 200);
+        reveal_at(&mut contract, alice(), outcome, 1499);
+    }
+
+    #[test]
+    #[should_panic(expected = "Revealed outcome and salt do not match the commitment")]
+    fn dr_commit_reveal_wrong_outcome() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_commit_reveal(&mut contract);
+
+        commit(&mut contract, alice(), &data_request::Outcome::Answer(AnswerType::String("a".to_string())), 200);
+        reveal_at(&mut contract, alice(), data_request::Outcome::Answer(AnswerType::String("b".to_string())), 1500);
+    }
+
+    #[test]
+    fn dr_commit_reveal_bonds_on_reveal() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_commit_reveal(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        commit(&mut contract, alice(), &outcome, 200);
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        let round0 : ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(round0.commit_end_time, Some(1500));
+        assert_eq!(round0.end_time, 2500);
+        assert!(round0.bonded_outcome.is_none());
+
+        reveal_at(&mut contract, alice(), outcome.clone(), 1500);
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.resolution_windows.len(), 2);
+        assert_eq!(request.resolution_windows.get(0).unwrap().bonded_outcome, Some(outcome));
+
+        let round1 : ResolutionWindow = request.resolution_windows.get(1).unwrap();
+        assert_eq!(round1.commit_end_time, Some(2500));
+        assert_eq!(round1.end_time, 3500);
+    }
+
+    #[test]
+    fn dr_commit_reveal_slash_unrevealed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = config();
+        config.slash_unrevealed_stake = true;
+        let mut contract = Contract::new(whitelist, config);
+        dr_new_commit_reveal(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        commit(&mut contract, alice(), &outcome, 200);
+        commit(&mut contract, carol(), &data_request::Outcome::Answer(AnswerType::String("b".to_string())), 50);
+        reveal_at(&mut contract, alice(), outcome, 1500);
+
+        let contract = finalize(&mut contract, 0);
+        let mut d = contract.data_requests.get(0).unwrap();
+        // own stake + carol's unrevealed stake
+        assert_eq!(sum_claim_res(d.claim(alice())), 250);
+        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+    }
+
+    #[test]
+    fn dr_commit_reveal_slash_unrevealed_without_correct_stake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = config();
+        config.slash_unrevealed_stake = true;
+        let mut contract = Contract::new(whitelist, config);
+        dr_new_commit_reveal(&mut contract);
+
+        commit(&mut contract, carol(), &data_request::Outcome::Answer(AnswerType::String("b".to_string())), 50);
+        expire_at(&mut contract, 5000);
+
+        // nobody staked on the final outcome, so carol's unrevealed stake is returned
+        let mut d = contract.data_requests.get(0).unwrap();
+        assert_eq!(sum_claim_res(d.claim(carol())), 50);
+        assert_eq!(d.stake_balance, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Final arbitrator is invoked for `DataRequest` with id: 0")]
+    fn dr_commit_reveal_reveal_after_final_arbitrator_invoked() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_commit_reveal(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        commit(&mut contract, alice(), &outcome, 200);
+        let mut d = contract.data_requests.get(0).unwrap();
+        d.final_arbitrator_triggered = true;
+        contract.data_requests.replace(0, &d);
+
+        reveal_at(&mut contract, alice(), outcome, 1500);
+    }
+
+    #[test]
+    fn dr_commit_reveal_refund_unrevealed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_commit_reveal(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        commit(&mut contract, alice(), &outcome, 200);
+        commit(&mut contract, carol(), &data_request::Outcome::Answer(AnswerType::String("b".to_string())), 50);
+        reveal_at(&mut contract, alice(), outcome, 1500);
+
+        let contract = finalize(&mut contract, 0);
+        let mut d = contract.data_requests.get(0).unwrap();
+        assert_eq!(sum_claim_res(d.claim(alice())), 200);
+        assert_eq!(sum_claim_res(d.claim(carol())), 50);
+        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+    }
+
+    #[test]
+    fn dr_cancel_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.cancelled);
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 is cancelled")]
+    fn dr_cancel_no_stake_after() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+
+        testing_env!(get_context(token()));
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator or requestor can cancel `DataRequest` with id: 0")]
+    fn dr_cancel_unauthorized() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(alice()));
+        contract.dr_cancel(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 already received stake and can't be cancelled")]
+    fn dr_cancel_after_stake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+    }
+
+    fn dr_new_with_settlement_time(contract : &mut Contract, settlement_time: u64) {
+        contract.dr_new(bob(), 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: Some(U64(settlement_time)),
+            expiry_fallback: None,
+            target_contract: None,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement time exceeds maximum settlement time offset of 10000")]
+    fn dr_new_settlement_time_exceed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_settlement_time(&mut contract, 10001);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't stake on `DataRequest` with id: 0 before settlement time 5000")]
+    fn dr_stake_before_settlement_time() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_settlement_time(&mut contract, 5000);
+
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 4999;
+        testing_env!(ct);
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+    }
+
+    #[test]
+    fn dr_stake_after_settlement_time() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_settlement_time(&mut contract, 5000);
+
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 5000;
+        testing_env!(ct);
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.settlement_time, Some(U64(5000)));
+        assert_eq!(request.resolution_windows[0].start_time, U64(5000));
+    }
+
+    #[test]
+    fn dr_claim_many_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+
+        for id in 0..2 {
+            contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+                id: U64(id),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+            });
+            let mut dr = contract.dr_get_expect(U64(id));
+            dr.finalize();
+            contract.data_requests.replace(id, &dr);
+        }
+
+        contract.dr_claim_many(alice(), vec![U64(0), U64(1)]);
+
+        for id in 0..2 {
+            let mut d = contract.data_requests.get(id).unwrap();
+            assert_eq!(sum_claim_res(d.claim(alice())), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only claim between 1 and 20 requests at once")]
+    fn dr_claim_many_too_many() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_claim_many(alice(), (0..21).map(U64).collect());
+    }
+
+    #[test]
+    fn dr_account_positions() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+
+        let outcome_a = data_request::Outcome::Answer(AnswerType::String("a".to_string()));
+        let outcome_b = data_request::Outcome::Answer(AnswerType::String("b".to_string()));
+        contract.dr_stake(alice(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: outcome_b.clone()
+        });
+        // only the part that fills the bond is indexed
+        contract.dr_stake(alice(), 300, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: outcome_a.clone()
+        });
+        contract.dr_stake(alice(), 20, StakeDataRequestArgs{
+            id: U64(1),
+            outcome: outcome_b.clone()
+        });
+
+        let positions = contract.get_account_positions(alice(), U64(0), U64(10));
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].request_id, U64(0));
+        assert_eq!(positions[0].positions.len(), 2);
+        assert_eq!(positions[0].positions[1].outcome, outcome_a);
+        assert_eq!(positions[0].positions[1].amount, U128(200));
+        assert!(!positions[0].claimable);
+        assert_eq!(contract.get_account_positions(alice(), U64(1), U64(10))[0].positions[0].amount, U128(20));
+
+        testing_env!(get_context(alice()));
+        contract.dr_unstake(U64(1), 0, outcome_b, U128(20));
+        assert_eq!(contract.get_account_positions(alice(), U64(0), U64(10)).len(), 1);
+
+        let mut dr = contract.dr_get_expect(U64(0));
+        dr.finalize();
+        contract.data_requests.replace(0, &dr);
+        assert!(contract.get_account_positions(alice(), U64(0), U64(10))[0].claimable);
+
+        contract.dr_claim(alice(), U64(0));
+        let positions = contract.get_account_positions(alice(), U64(0), U64(10));
+        assert!(!positions[0].claimable);
+        assert!(positions[0].claimed);
+    }
+
+    fn dr_new_tagged(contract : &mut Contract, requestor: AccountId, tags: Vec<String>) {
+        contract.dr_new(requestor, 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: Some(tags),
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
+        });
+    }
+
+    fn page_ids(page: &RequestPage) -> Vec<u64> {
+        page.requests.iter().map(|request| request.id).collect()
+    }
+
+    #[test]
+    fn dr_filtered_by_requestor_and_tag() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_tagged(&mut contract, bob(), vec!["eth".to_string()]);
+        dr_new_tagged(&mut contract, carol(), vec!["eth".to_string(), "eth".to_string()]);
+        dr_new_tagged(&mut contract, bob(), vec!["btc".to_string()]);
+        dr_new_tagged(&mut contract, bob(), vec!["eth".to_string()]);
+
+        let page = contract.get_requests_filtered(RequestFilter::Requestor(bob()), None, U64(2), None);
+        assert_eq!(page_ids(&page), vec![0, 2]);
+        assert_eq!(page.next_cursor, Some(U64(2)));
+        let page = contract.get_requests_filtered(RequestFilter::Requestor(bob()), page.next_cursor, U64(2), None);
+        assert_eq!(page_ids(&page), vec![3]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = contract.get_requests_filtered(RequestFilter::Tag("eth".to_string()), None, U64(10), Some(true));
+        assert_eq!(page_ids(&page), vec![3, 1, 0]);
+        let page = contract.get_requests_filtered(RequestFilter::Tag("eth".to_string()), Some(U64(3)), U64(10), Some(true));
+        assert_eq!(page_ids(&page), vec![1, 0]);
+
+        let page = contract.get_requests_filtered(RequestFilter::Creator(carol()), None, U64(10), None);
+        assert_eq!(page_ids(&page), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn dr_filtered_by_state() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(1),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        let page = contract.get_requests_filtered(RequestFilter::State(DataRequestState::AwaitingFirstStake), None, U64(10), None);
+        assert_eq!(page_ids(&page), vec![0, 2]);
+        let page = contract.get_requests_filtered(RequestFilter::State(DataRequestState::InDispute), None, U64(10), None);
+        assert_eq!(page_ids(&page), vec![1]);
+
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 1000;
+        testing_env!(ct);
+        let page = contract.get_requests_filtered(RequestFilter::State(DataRequestState::Finalizable), None, U64(10), None);
+        assert_eq!(page_ids(&page), vec![1]);
+        assert_eq!(page.requests[0].state, DataRequestState::Finalizable);
+
+        contract.dr_finalize(U64(1));
+        let page = contract.get_requests_filtered(RequestFilter::State(DataRequestState::Finalized), None, U64(10), None);
+        assert_eq!(page_ids(&page), vec![1]);
+        let page = contract.get_requests_filtered(RequestFilter::State(DataRequestState::InDispute), None, U64(10), None);
+        assert_eq!(page_ids(&page), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn dr_linear_bond_escalation() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = config();
+        config.bond_escalation = oracle_config::BondEscalation::Linear(U128(20));
+        let mut contract = Contract::new(whitelist, config);
+        dr_new(&mut contract);
+
+        contract.dr_stake(alice(), 120, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.resolution_windows[0].bond_size, U128(120));
+        assert_eq!(request.resolution_windows[1].bond_size, U128(140));
+        assert_eq!(request.request_config.bond_escalation, oracle_config::BondEscalation::Linear(U128(20)));
+    }
+
+    fn expire_at(contract: &mut Contract, timestamp: u64) {
+        let mut ct : VMContext = get_context(alice());
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.dr_expire(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 can't expire before 5000")]
+    fn dr_expire_too_early() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+
+        expire_at(&mut contract, 4999);
+    }
+
+    #[test]
+    fn dr_expire_invalid() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+
+        expire_at(&mut contract, 5000);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.expired);
+        assert_eq!(contract.get_outcome(U64(0)), data_request::Outcome::Invalid);
+    }
+
+    #[test]
+    fn dr_expire_fallback_after_settlement_time() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        let fallback = data_request::Outcome::Answer(AnswerType::String("b".to_string()));
+        contract.dr_new(bob(), 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: Some(U64(1000)),
+            expiry_fallback: Some(fallback.clone()),
+            target_contract: None,
+        });
+
+        expire_at(&mut contract, 6000);
+
+        assert_eq!(contract.get_outcome(U64(0)), fallback);
+    }
+
+    #[test]
+    fn dr_expire_partial_stake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(alice(), 150, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        expire_at(&mut contract, 5000);
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(150));
+
+        // the partial stake is refunded on claim, after which the request can be archived right away
+        contract.dr_claim(alice(), U64(0));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(0));
+        assert!(contract.dr_archive(U64(0), U64(100)));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 has a bonded outcome and can't expire")]
+    fn dr_expire_bonded() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        expire_at(&mut contract, 5000);
+    }
+
+    fn dr_new_with_tolerance(contract : &mut Contract, data_type: DataRequestDataType, tolerance: NumberTolerance) {
+        contract.dr_new(bob(), 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: None,
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: Some(tolerance),
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
+        });
+    }
+
+    fn number_outcome(value: u128) -> Outcome {
+        data_request::Outcome::Answer(AnswerType::Number(AnswerNumberType {
+            value: U128(value),
+            multiplier: U128(1),
+            negative: false,
+        }))
+    }
+
+    #[test]
+    #[should_panic(expected = "Tolerance can only be set for number data requests")]
+    fn dr_tolerance_string_data_type() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::String, NumberTolerance::Absolute(U128(5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Relative tolerance should be between 1 and 10000")]
+    fn dr_tolerance_relative_exceeds_max() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Relative(10_001));
+    }
+
+    #[test]
+    fn dr_tolerance_pools_close_answers() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Absolute(U128(5)));
+
+        contract.dr_stake(alice(), 120, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(100)
+        });
+        contract.dr_stake(carol(), 80, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(103)
+        });
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.resolution_windows.len(), 2);
+        // stake weighted median of the bucket
+        assert_eq!(request.resolution_windows.get(0).unwrap().bonded_outcome, Some(number_outcome(100)));
+
+        let contract = finalize(&mut contract, 0);
+        let mut d = contract.data_requests.get(0).unwrap();
+        assert_eq!(sum_claim_res(d.claim(alice())), 120);
+        assert_eq!(sum_claim_res(d.claim(carol())), 80);
+    }
+
+    #[test]
+    fn dr_tolerance_far_answers_not_pooled() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Relative(100));
+
+        contract.dr_stake(alice(), 120, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(1000)
+        });
+        contract.dr_stake(carol(), 80, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(1011)
+        });
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.resolution_windows.len(), 1);
+        assert!(request.resolution_windows.get(0).unwrap().bonded_outcome.is_none());
+    }
+
+    #[test]
+    fn dr_tolerance_pools_across_width_boundary() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Relative(100));
+
+        // 999 has a band of 9 and 1005 a band of 10, 1005's band contains 999
+        contract.dr_stake(alice(), 120, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(999)
+        });
+        contract.dr_stake(carol(), 80, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(1005)
+        });
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.resolution_windows.len(), 2);
+        assert_eq!(request.resolution_windows.get(0).unwrap().bonded_outcome, Some(number_outcome(999)));
+    }
+
+    #[test]
+    fn dr_tolerance_finds_nearest_anchor() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Relative(100));
+
+        // two separate buckets whose anchors have different band widths
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(905)
+        });
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(1000)
+        });
+        // only inside the band of 1000
+        contract.dr_stake(carol(), 190, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(1004)
+        });
+
+        let request : DataRequest = contract.data_requests.get(0).unwrap();
+        assert_eq!(request.resolution_windows.len(), 2);
+        assert_eq!(request.resolution_windows.get(0).unwrap().bonded_outcome, Some(number_outcome(1004)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Outcome is incompatible for this round")]
+    fn dr_tolerance_dispute_inside_band() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_tolerance(&mut contract, DataRequestDataType::Number(U128(1)), NumberTolerance::Absolute(U128(5)));
+
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(100)
+        });
+        contract.dr_stake(carol(), 400, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: number_outcome(104)
+        });
+    }
+
+    #[test]
+    fn dr_get_methods() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+        
+        assert_eq!(contract.get_latest_request().unwrap().id, 2);
+        assert_eq!(contract.get_request_by_id(U64(1)).unwrap().id, 1);
+
+        assert_eq!(contract.get_requests(U64(0), U64(1))[0].id, 0);
+        assert_eq!(contract.get_requests(U64(1), U64(1)).len(), 1);
+        assert_eq!(contract.get_requests(U64(1), U64(2)).len(), 2);
+        assert_eq!(contract.get_requests(U64(0), U64(3)).len(), 3);
+    }
+
+    fn finalize_at(contract: &mut Contract, timestamp: u64) {
+        let mut ct : VMContext = get_context(alice());
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+    }
+
+    fn arbitration_timeout_contract(fallback: ArbitrationFallback) -> Contract {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = config();
+        config.arbitration_timeout = U64(3000);
+        config.arbitration_fallback = fallback;
+        let mut contract = Contract::new(whitelist, config);
+        dr_new(&mut contract);
+
+        contract.dr_stake(alice(), 200, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+        // This round exceeds final arb limit, will be used as signal
+        contract.dr_stake(bob(), 400, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be finalized by final arbitrator: alice.near")]
+    fn dr_finalize_before_arbitration_timeout() {
+        let mut contract = arbitration_timeout_contract(ArbitrationFallback::LastBondedOutcome);
+        finalize_at(&mut contract, 2999);
+    }
+
+    #[test]
+    fn dr_finalize_arbitration_timeout_last_bonded() {
+        let mut contract = arbitration_timeout_contract(ArbitrationFallback::LastBondedOutcome);
+        finalize_at(&mut contract, 3000);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.finalized_outcome.unwrap(), data_request::Outcome::Answer(AnswerType::String("b".to_string())));
+        assert!(request.arbitration_fallback_used);
+
+        let mut d = contract.data_requests.get(0).unwrap();
+        // own stake (400), round0 (200)
+        assert_eq!(sum_claim_res(d.claim(bob())), 600);
+    }
+
+    #[test]
+    fn dr_finalize_arbitration_timeout_invalid() {
+        let mut contract = arbitration_timeout_contract(ArbitrationFallback::Invalid);
+        finalize_at(&mut contract, 3000);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.finalized_outcome.unwrap(), data_request::Outcome::Invalid);
+        assert!(request.arbitration_fallback_used);
+    }
+
+    fn finalize_and_deliver(contract: &mut Contract) {
+        contract.dr_stake(alice(), 2000, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 1501;
+        testing_env!(ct);
+
+        contract.dr_finalize(U64(0));
+    }
+
+    #[test]
+    fn dr_finalize_delivers_outcome() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        assert!(contract.get_request_by_id(U64(0)).unwrap().outcome_delivery.is_none());
+
+        finalize_and_deliver(&mut contract);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Pending));
+        assert!(contract.get_failed_outcome_deliveries(U64(0), U64(10)).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Outcome delivery of `DataRequest` with id: 0 didn't fail")]
+    fn dr_retry_outcome_delivery_not_failed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        contract.dr_retry_outcome_delivery(U64(0), U64(25_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Retrying the outcome delivery of `DataRequest` with id: 0 requires an attached deposit of 1")]
+    fn dr_retry_outcome_delivery_wrong_deposit() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+        contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::Requestor], vec![PromiseResult::Failed]);
+
+        contract.dr_retry_outcome_delivery(U64(0), U64(25_000_000_000_000));
+    }
+
+    #[test]
+    fn dr_outcome_delivery_failed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        assert!(!contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::Requestor], vec![PromiseResult::Failed]));
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Failed));
+        assert_eq!(contract.get_failed_outcome_deliveries(U64(0), U64(10)), vec![U64(0)]);
+    }
+
+    #[test]
+    fn dr_outcome_delivery_delivered() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        assert!(contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::Requestor], vec![PromiseResult::Successful(vec![])]));
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Delivered));
+        assert!(contract.get_failed_outcome_deliveries(U64(0), U64(10)).is_empty());
+    }
+
+    #[test]
+    fn dr_retry_outcome_delivery_succeeds() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+        contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::Requestor], vec![PromiseResult::Failed]);
+
+        let mut ct : VMContext = get_context(alice());
+        ct.attached_deposit = 1;
+        testing_env!(ct);
+        contract.dr_retry_outcome_delivery(U64(0), U64(25_000_000_000_000));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Pending));
+        assert!(contract.get_failed_outcome_deliveries(U64(0), U64(10)).is_empty());
+
+        assert!(contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::Requestor], vec![PromiseResult::Successful(vec![])]));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Delivered));
+        assert!(contract.get_failed_outcome_deliveries(U64(0), U64(10)).is_empty());
+    }
+
+    fn dr_new_with_target_contract(contract: &mut Contract, gas: u64) {
+        contract.dr_new(bob(), 100, NewDataRequestArgs{
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: data_request::DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: Some(TargetContract {
+                account_id: dave(),
+                method_name: "on_outcome".to_string(),
+                gas: U64(gas),
+                also_notify_requestor: true,
+            }),
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Target contract gas should be between 1 and 100000000000000")]
+    fn dr_new_target_contract_too_much_gas() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_target_contract(&mut contract, 100_000_000_000_001);
+    }
+
+    #[test]
+    fn dr_finalize_delivers_to_target_contract() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_target_contract(&mut contract, 10_000_000_000_000);
+        finalize_and_deliver(&mut contract);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.target_contract.unwrap().account_id, dave());
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Pending));
+        assert_eq!(contract.data_requests.get(0).unwrap().outcome_recipients(), vec![OutcomeRecipient::Requestor, OutcomeRecipient::TargetContract]);
+    }
+
+    #[test]
+    fn dr_retry_outcome_delivery_only_failed_recipient() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new_with_target_contract(&mut contract, 10_000_000_000_000);
+        finalize_and_deliver(&mut contract);
+
+        let recipients = vec![OutcomeRecipient::Requestor, OutcomeRecipient::TargetContract];
+        assert!(!contract.record_outcome_delivery(U64(0), recipients, vec![PromiseResult::Successful(vec![]), PromiseResult::Failed]));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Failed));
+        assert_eq!(request.undelivered_recipients, vec![OutcomeRecipient::TargetContract]);
+
+        let mut ct : VMContext = get_context(alice());
+        ct.attached_deposit = 1;
+        testing_env!(ct);
+        contract.dr_retry_outcome_delivery(U64(0), U64(25_000_000_000_000));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().undelivered_recipients, vec![OutcomeRecipient::TargetContract]);
+
+        assert!(contract.record_outcome_delivery(U64(0), vec![OutcomeRecipient::TargetContract], vec![PromiseResult::Successful(vec![])]));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.outcome_delivery, Some(OutcomeDelivery::Delivered));
+        assert!(request.undelivered_recipients.is_empty());
+    }
+
+    #[test]
+    fn dr_archive_after_all_claimed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        contract.dr_claim(alice(), U64(0));
+        assert!(contract.dr_archive(U64(0), U64(100)));
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.archived);
+        assert!(request.resolution_windows.is_empty());
+        assert_eq!(request.finalized_outcome.unwrap(), data_request::Outcome::Answer(AnswerType::String("a".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 has unclaimed stake and can't be archived before 6501")]
+    fn dr_archive_unclaimed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        contract.dr_archive(U64(0), U64(100));
+    }
+
+    #[test]
+    fn dr_archive_releases_participant_storage() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+        contract.dr_claim(alice(), U64(0));
+
+        let mut ct : VMContext = get_context(carol());
+        ct.block_timestamp = 1502;
+        testing_env!(ct);
+        assert!(contract.dr_archive(U64(0), U64(100)));
+
+        assert!(contract.get_storage_account(&alice()).available > 0);
+        assert_eq!(contract.get_storage_account(&carol()).available, 0);
+    }
+
+    #[test]
+    fn dr_archive_paginated() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(bob(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        finalize_and_deliver(&mut contract);
+        contract.dr_claim(alice(), U64(0));
+        contract.dr_claim(bob(), U64(0));
+
+        // alice and bob, then the outcomes "a" and "b" of the first window
+        assert!(!contract.dr_archive(U64(0), U64(1)));
+        assert!(!contract.dr_archive(U64(0), U64(1)));
+        assert!(!contract.dr_archive(U64(0), U64(1)));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.archived);
+        assert_eq!(request.resolution_windows.len(), 1);
+
+        assert!(contract.dr_archive(U64(0), U64(1)));
+        assert!(contract.get_request_by_id(U64(0)).unwrap().resolution_windows.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 has unclaimed stake and can't be archived before 6501")]
+    fn dr_archive_unclaimed_unbonded_refund() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(bob(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        finalize_and_deliver(&mut contract);
+
+        // all correct stake is claimed, bob's unbonded stake is still owed
+        contract.dr_claim(alice(), U64(0));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(50));
+        contract.dr_archive(U64(0), U64(100));
+    }
+
+    #[test]
+    fn dr_archive_after_unstake_and_all_claimed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(bob(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+        finalize_and_deliver(&mut contract);
+
+        let mut ct : VMContext = get_context(bob());
+        ct.block_timestamp = 1501;
+        testing_env!(ct);
+        contract.dr_unstake(U64(0), 0, data_request::Outcome::Answer(AnswerType::String("b".to_string())), U128(20));
+        contract.dr_claim(alice(), U64(0));
+        contract.dr_claim(bob(), U64(0));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(0));
+
+        assert!(contract.dr_archive(U64(0), U64(100)));
+        assert!(contract.get_request_by_id(U64(0)).unwrap().archived);
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 is archived")]
+    fn dr_archive_after_claim_window() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 6501;
+        testing_env!(ct);
+        contract.dr_archive(U64(0), U64(100));
+        assert!(contract.get_request_by_id(U64(0)).unwrap().archived);
+
+        contract.dr_claim(alice(), U64(0));
+    }
+
+    fn sweep_at(contract: &mut Contract, timestamp: u64) -> Option<Promise> {
+        let mut ct : VMContext = get_context(alice());
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.dr_sweep(U64(0))
+    }
+
+    #[test]
+    fn dr_sweep_after_claim_deadline() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.claim_deadline, Some(U64(6501)));
+        // validity bond is returned on a valid outcome, alice never claimed her bonded stake
+        assert_eq!(request.payment_balance, U128(0));
+        assert_eq!(request.stake_balance, U128(200));
+
+        assert!(sweep_at(&mut contract, 6501).is_some());
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.swept);
+        assert_eq!(request.stake_balance, U128(0));
+    }
+
+    #[test]
+    fn dr_sweep_failed_transfer_restored() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+        sweep_at(&mut contract, 6501);
+
+        assert!(!contract.record_sweep(U64(0), 0, 200, vec![PromiseResult::Failed]));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(!request.swept);
+        assert_eq!(request.stake_balance, U128(200));
+
+        assert!(sweep_at(&mut contract, 6501).is_some());
+        assert!(contract.record_sweep(U64(0), 0, 200, vec![PromiseResult::Successful(vec![])]));
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert!(request.swept);
+        assert_eq!(request.stake_balance, U128(0));
+    }
+
+    #[test]
+    fn dr_sweep_after_unstake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        contract.dr_stake(bob(), 50, StakeDataRequestArgs{
+            id: U64(0),
+            outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string()))
+        });
+
+        testing_env!(get_context(bob()));
+        contract.dr_unstake(U64(0), 0, data_request::Outcome::Answer(AnswerType::String("b".to_string())), U128(20));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(30));
+
+        testing_env!(get_context(token()));
+        finalize_and_deliver(&mut contract);
+
+        // alice's bonded stake and bob's remaining unbonded stake
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(230));
+        assert!(sweep_at(&mut contract, 6501).is_some());
+        assert!(!contract.record_sweep(U64(0), 0, 230, vec![PromiseResult::Failed]));
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().stake_balance, U128(230));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 can't be swept before its claim deadline 6501")]
+    fn dr_sweep_before_claim_deadline() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        sweep_at(&mut contract, 6500);
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 is swept, its claim window has closed")]
+    fn dr_claim_after_sweep() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        sweep_at(&mut contract, 6501);
+        contract.dr_claim(alice(), U64(0));
+    }
+
+    fn set_claiming_paused_at(contract: &mut Contract, paused: bool, timestamp: u64) {
+        let mut ct : VMContext = get_context(gov());
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.set_pause_state(pause::PauseState {
+            claiming: paused,
+            ..pause::PauseState::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim is paused")]
+    fn dr_sweep_claims_paused() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        set_claiming_paused_at(&mut contract, true, 6501);
+        sweep_at(&mut contract, 6501);
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim is paused")]
+    fn dr_archive_claims_paused() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+        contract.dr_claim(alice(), U64(0));
+
+        set_claiming_paused_at(&mut contract, true, 2000);
+        contract.dr_archive(U64(0), U64(100));
+    }
+
+    #[test]
+    fn dr_claim_deadline_extended_by_pause() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        // a pause that ended before the finalization doesn't count
+        set_claiming_paused_at(&mut contract, true, 100);
+        set_claiming_paused_at(&mut contract, false, 200);
+
+        // an ongoing pause keeps pushing the deadline back
+        set_claiming_paused_at(&mut contract, true, 2000);
+        let mut ct : VMContext = get_context(alice());
+        ct.block_timestamp = 3000;
+        testing_env!(ct);
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().claim_deadline, Some(U64(7501)));
+
+        set_claiming_paused_at(&mut contract, false, 4000);
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().claim_deadline, Some(U64(8501)));
+
+        // a pause that starts after the deadline doesn't extend it
+        set_claiming_paused_at(&mut contract, true, 9000);
+        set_claiming_paused_at(&mut contract, false, 9500);
+        assert_eq!(contract.get_request_by_id(U64(0)).unwrap().claim_deadline, Some(U64(8501)));
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest` with id: 0 can't be swept before its claim deadline 8501")]
+    fn dr_sweep_before_extended_claim_deadline() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        finalize_and_deliver(&mut contract);
+
+        set_claiming_paused_at(&mut contract, true, 2000);
+        set_claiming_paused_at(&mut contract, false, 4000);
+        sweep_at(&mut contract, 6501);
     }
 }
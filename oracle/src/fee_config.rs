@@ -13,33 +13,46 @@ pub struct FeeConfig {
     pub resolution_fee_percentage: u32,
 }
 
+impl FeeConfig {
+    pub fn assert_valid(&self) {
+        assert!(
+            u128::from(self.total_value_staked) < u128::from(self.flux_market_cap),
+            "TVS must be lower than market cap"
+        );
+        assert!(
+            self.resolution_fee_percentage <= MAX_RESOLUTION_FEE_PERCENTAGE,
+            "Exceeds max resolution fee percentage"
+        );
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     // @notice sets FLUX market cap, TVS, and fee percentage by updating current oracle config
-    // replaces the `fee` field inside oracle config with updated FeeConfig 
+    // replaces the `fee` field inside oracle config with updated FeeConfig, or schedules the
+    // change when the current config has a `config_change_delay`
+    #[payable]
     pub fn update_fee_config(
         &mut self,
         new_fee_config: FeeConfig,
     ) {
-        self.assert_gov();
+        self.assert_role(governance::Role::FeeAdmin);
 
         let initial_storage = env::storage_usage();
 
-        assert!(
-            u128::from(new_fee_config.total_value_staked) < u128::from(new_fee_config.flux_market_cap),
-            "TVS must be lower than market cap"
-        );
-        assert!(
-            new_fee_config.resolution_fee_percentage <= MAX_RESOLUTION_FEE_PERCENTAGE,
-            "Exceeds max resolution fee percentage"
-        );
-
         // get current config and replace fee field
         let mut updated_config = self.get_config();
-        updated_config.fee = new_fee_config.clone();
-        self.configs.replace(self.configs.len() - 1, &updated_config);
+        updated_config.fee = new_fee_config;
+        updated_config.assert_valid();
+
+        let delay: u64 = updated_config.config_change_delay.into();
+        if delay == 0 {
+            self.configs.replace(self.configs.len() - 1, &updated_config);
+            logger::log_oracle_config(&updated_config, self.configs.len() - 1);
+        } else {
+            self.schedule_config(updated_config, delay);
+        }
 
-        logger::log_oracle_config(&updated_config, self.configs.len() - 1);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
     }
 }
@@ -79,6 +92,17 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
@@ -133,4 +157,26 @@ mod mock_token_basic_tests {
         };
         contract.update_fee_config(new_fee_config);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn g_update_fee_delayed() {
+        testing_env!(get_context(gov()));
+        let mut c = config(gov());
+        c.config_change_delay = U64(1000);
+        let mut contract = Contract::new(None, c);
+        let new_fee_config = FeeConfig {
+            flux_market_cap: U128(1234),
+            total_value_staked: U128(123),
+            resolution_fee_percentage: 999, // .999%
+        };
+        contract.update_fee_config(new_fee_config);
+        assert_eq!(contract.get_config().fee.resolution_fee_percentage, 5000);
+        assert_eq!(contract.get_pending_config().unwrap().config.fee.resolution_fee_percentage, 999);
+
+        let mut context = get_context(bob());
+        context.block_timestamp = 1000;
+        testing_env!(context);
+        contract.activate_pending_config();
+        assert_eq!(contract.get_config().fee.resolution_fee_percentage, 999);
+    }
+}
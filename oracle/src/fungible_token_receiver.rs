@@ -7,7 +7,9 @@ use near_sdk::PromiseOrValue;
 #[derive(Serialize, Deserialize)]
 pub enum Payload {
     NewDataRequest(NewDataRequestArgs),
-    StakeDataRequest(StakeDataRequestArgs)
+    StakeDataRequest(StakeDataRequestArgs),
+    CommitDataRequest(CommitDataRequestArgs),
+    NewRecurringRequest(recurring_request::NewRecurringRequestArgs)
 }
 
 pub trait FungibleTokenReceiver {
@@ -24,13 +26,25 @@ impl FungibleTokenReceiver for Contract {
         amount: U128,
         msg: String
     ) -> PromiseOrValue<WrappedBalance> {
+        let payload: Payload =  serde_json::from_str(&msg).expect("Failed to parse the payload, invalid `msg` format");
+
+        // transfers for paused operations are returned in full so the token refunds them
+        let operation = match &payload {
+            Payload::NewDataRequest(_) | Payload::NewRecurringRequest(_) => pause::Operation::NewRequest,
+            Payload::StakeDataRequest(_) | Payload::CommitDataRequest(_) => pause::Operation::Stake,
+        };
+        if self.pause_state.is_paused(operation) {
+            return PromiseOrValue::Value(amount);
+        }
+
         let initial_storage_usage = env::storage_usage();
         let account = self.get_storage_account(&sender_id);
 
-        let payload: Payload =  serde_json::from_str(&msg).expect("Failed to parse the payload, invalid `msg` format");
         let unspent = match payload {
             Payload::NewDataRequest(payload) => self.ft_dr_new_callback(sender_id.clone(), amount.into(), payload).into(),
             Payload::StakeDataRequest(payload) => self.dr_stake(sender_id.clone(), amount.into(), payload),
+            Payload::CommitDataRequest(payload) => self.dr_commit(sender_id.clone(), amount.into(), payload),
+            Payload::NewRecurringRequest(payload) => self.dr_new_recurring(sender_id.clone(), amount.into(), payload),
         };
 
         self.use_storage(&sender_id, initial_storage_usage, account.available);
@@ -80,7 +94,9 @@ mod mock_token_basic_tests {
             interface_name: account.clone(),
             account_id: account.clone(),
             stake_multiplier: None,
-            code_base_url: None
+            code_base_url: None,
+            callback: None,
+            overrides: None
         }
     }
 
@@ -95,6 +111,17 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(250),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
@@ -139,6 +166,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
 
         let msg = serde_json::json!({
@@ -164,6 +196,11 @@ mod mock_token_basic_tests {
             tags: None,
             data_type: data_request::DataRequestDataType::String,
             creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
         });
 
         let storage_start = 10u128.pow(24);
@@ -185,4 +222,29 @@ mod mock_token_basic_tests {
         let account = contract.accounts.get(&alice());
         assert!(account.unwrap().available < storage_start);
     }
+
+    #[test]
+    fn transfer_paused_returns_amount() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+
+        testing_env!(get_context(gov()));
+        contract.set_pause_state(pause::PauseState {
+            staking: true,
+            ..pause::PauseState::default()
+        });
+
+        testing_env!(get_context(token()));
+        let msg = serde_json::json!({
+            "StakeDataRequest": {
+                "id": "0",
+                "outcome": Outcome::Answer(AnswerType::String("a".to_string()))
+            }
+        });
+        match contract.ft_on_transfer(alice(), U128(100), msg.to_string()) {
+            PromiseOrValue::Value(unspent) => assert_eq!(unspent, U128(100)),
+            PromiseOrValue::Promise(_) => panic!("paused transfer should be returned")
+        };
+    }
 }
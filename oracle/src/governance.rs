@@ -0,0 +1,351 @@
+use crate::*;
+use near_sdk::serde::{ Deserialize, Serialize };
+
+/**
+ * @notice Permissions that gov can grant to other accounts, gov itself can always act as any role
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    ConfigAdmin, // `set_config` and `cancel_pending_config`
+    WhitelistAdmin, // adding, updating, removing and migrating whitelisted requestors
+    FeeAdmin, // `update_fee_config`
+    Pauser, // `set_pause_state`
+    Upgrader, // `upgrade`
+}
+
+pub const ROLES: [Role; 5] = [Role::ConfigAdmin, Role::WhitelistAdmin, Role::FeeAdmin, Role::Pauser, Role::Upgrader];
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_gov();
+        assert!(env::is_valid_account_id(account_id.as_bytes()), "Invalid account id: {}", account_id);
+
+        let initial_storage = env::storage_usage();
+
+        let mut members = self.role_members(role);
+        members.insert(&account_id);
+        self.roles.insert(&role, &members);
+
+        logger::log_role(&account_id, role, true);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    #[payable]
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_gov();
+
+        let initial_storage = env::storage_usage();
+
+        let mut members = self.role_members(role);
+        assert!(members.remove(&account_id), "{} doesn't have the {:?} role", account_id, role);
+        self.roles.insert(&role, &members);
+
+        logger::log_role(&account_id, role, false);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.role_members(role).contains(&account_id)
+    }
+
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        ROLES
+            .iter()
+            .filter(|role| self.role_members(**role).contains(&account_id))
+            .copied()
+            .collect()
+    }
+
+    pub fn get_role_members(&self, role: Role, from_index: U64, limit: U64) -> Vec<AccountId> {
+        let members = self.role_members(role);
+        let members = members.as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i.saturating_add(limit.into()), members.len()))
+            .map(|index| members.get(index).unwrap())
+            .collect()
+    }
+    /**
+     * @notice Proposes `new_gov` as the governance account, the handover only takes effect once `new_gov` calls `accept_gov`
+     */
+    #[payable]
+    pub fn propose_gov(&mut self, new_gov: AccountId) {
+        self.assert_gov();
+        assert!(env::is_valid_account_id(new_gov.as_bytes()), "Invalid governance account id: {}", new_gov);
+
+        let initial_storage = env::storage_usage();
+
+        self.pending_gov = Some(new_gov.to_string());
+
+        logger::log_gov_proposal(&self.get_config().gov, Some(&new_gov));
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    /**
+     * @notice Completes the handover, pushes a copy of the current config with the proposed account as `gov`
+     */
+    #[payable]
+    pub fn accept_gov(&mut self) {
+        let initial_storage = env::storage_usage();
+
+        let new_gov = self.pending_gov.take().expect("There is no pending governance handover");
+        assert_eq!(
+            env::predecessor_account_id(),
+            new_gov,
+            "Only the proposed governance account {} can accept the handover",
+            new_gov
+        );
+
+        let mut config = self.get_config();
+        config.gov = new_gov.to_string();
+        self.configs.push(&config);
+
+        // a config scheduled by the previous gov shouldn't hand governance back once activated
+        if let Some(pending) = &mut self.pending_config {
+            pending.config.gov = new_gov.to_string();
+        }
+
+        logger::log_gov_proposal(&new_gov, None);
+        logger::log_oracle_config(&config, self.configs.len() - 1);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    #[payable]
+    pub fn cancel_gov_proposal(&mut self) {
+        self.assert_gov();
+
+        let initial_storage = env::storage_usage();
+
+        self.pending_gov.take().expect("There is no pending governance handover");
+
+        logger::log_gov_proposal(&self.get_config().gov, None);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    pub fn get_pending_gov(&self) -> Option<AccountId> {
+        self.pending_gov.clone()
+    }
+}
+
+impl Contract {
+    /**
+     * @notice Passes for gov and for accounts that were granted `role`
+     */
+    pub fn assert_role(&self, role: Role) {
+        let caller = env::predecessor_account_id();
+        if self.role_members(role).contains(&caller) {
+            return;
+        }
+
+        let config = self.get_config();
+        assert_eq!(
+            config.gov,
+            caller,
+            "This method is only callable by the governance contract {} or accounts with the {:?} role",
+            config.gov,
+            role
+        );
+    }
+
+    fn role_members(&self, role: Role) -> UnorderedSet<AccountId> {
+        self.roles.get(&role).unwrap_or_else(|| {
+            UnorderedSet::new(format!("ro{}", role as u8).as_bytes().to_vec())
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use near_sdk::{ MockedBlockchain };
+    use near_sdk::{ testing_env, VMContext };
+    use fee_config::FeeConfig;
+    use super::*;
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn bob() -> AccountId {
+        "bob.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn gov() -> AccountId {
+        "gov.near".to_string()
+    }
+
+    fn config(gov: AccountId) -> oracle_config::OracleConfig {
+        oracle_config::OracleConfig {
+            gov,
+            final_arbitrator: alice(),
+            payment_token: token(),
+            stake_token: token(),
+            validity_bond: U128(0),
+            max_outcomes: 8,
+            default_challenge_window_duration: U64(1000),
+            min_initial_challenge_window_duration: U64(1000),
+            final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
+            fee: FeeConfig {
+                flux_market_cap: U128(50000),
+                total_value_staked: U128(10000),
+                resolution_fee_percentage: 5000, // 5%
+            }
+        }
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: token(),
+            signer_account_id: bob(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 1000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 19000000000000000000000,
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    #[test]
+    fn gov_handover() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.propose_gov(alice());
+        assert_eq!(contract.get_pending_gov(), Some(alice()));
+        assert_eq!(contract.get_config().gov, gov());
+
+        testing_env!(get_context(alice()));
+        contract.accept_gov();
+        assert_eq!(contract.get_config().gov, alice());
+        assert!(contract.get_pending_gov().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed governance account alice.near can accept the handover")]
+    fn accept_gov_from_other_account() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.propose_gov(alice());
+
+        testing_env!(get_context(bob()));
+        contract.accept_gov();
+    }
+
+    #[test]
+    #[should_panic(expected = "There is no pending governance handover")]
+    fn accept_gov_after_cancel() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.propose_gov(alice());
+        contract.cancel_gov_proposal();
+
+        testing_env!(get_context(alice()));
+        contract.accept_gov();
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn propose_gov_from_user() {
+        testing_env!(get_context(alice()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.propose_gov(alice());
+    }
+
+    #[test]
+    fn accept_gov_updates_pending_config() {
+        testing_env!(get_context(gov()));
+        let mut c = config(gov());
+        c.config_change_delay = U64(1000);
+        let mut contract = Contract::new(None, c.clone());
+        contract.set_config(c);
+        contract.propose_gov(alice());
+
+        testing_env!(get_context(alice()));
+        contract.accept_gov();
+        assert_eq!(contract.get_pending_config().unwrap().config.gov, alice());
+    }
+
+    #[test]
+    fn grant_and_revoke_role() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), Role::ConfigAdmin);
+        contract.grant_role(alice(), Role::Pauser);
+        contract.grant_role(bob(), Role::Pauser);
+
+        assert!(contract.has_role(alice(), Role::ConfigAdmin));
+        assert_eq!(contract.get_roles(alice()), vec![Role::ConfigAdmin, Role::Pauser]);
+        assert_eq!(contract.get_role_members(Role::Pauser, U64(0), U64(10)), vec![alice(), bob()]);
+        assert_eq!(contract.get_role_members(Role::Pauser, U64(1), U64(10)), vec![bob()]);
+
+        contract.revoke_role(alice(), Role::Pauser);
+        assert_eq!(contract.get_roles(alice()), vec![Role::ConfigAdmin]);
+        assert_eq!(contract.get_role_members(Role::Pauser, U64(0), U64(10)), vec![bob()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near doesn't have the Upgrader role")]
+    fn revoke_missing_role() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.revoke_role(bob(), Role::Upgrader);
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn grant_role_from_user() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), Role::ConfigAdmin);
+
+        // roles can't grant roles, only gov can
+        testing_env!(get_context(alice()));
+        contract.grant_role(bob(), Role::ConfigAdmin);
+    }
+
+    #[test]
+    fn role_holder_passes_role_check() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), Role::FeeAdmin);
+
+        testing_env!(get_context(alice()));
+        contract.assert_role(Role::FeeAdmin);
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near or accounts with the WhitelistAdmin role")]
+    fn other_role_fails_role_check() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), Role::FeeAdmin);
+
+        testing_env!(get_context(alice()));
+        contract.assert_role(Role::WhitelistAdmin);
+    }
+}
@@ -4,8 +4,10 @@ use near_sdk::{
     StorageUsage,
     AccountId, 
     Balance,
-    Promise
+    Promise,
+    serde_json,
 };
+use crate::types::Outcome;
 
 const STORAGE_PRICE_PER_BYTE: Balance = 100_000_000_000_000_000_000;
 
@@ -45,6 +47,17 @@ pub fn refund_storage(initial_storage: StorageUsage, sender_id: AccountId) {
     }
 }
 
+// @notice Same as `refund_storage`, except freed storage is refunded to `owner_id` who paid for it instead of the sender
+pub fn refund_storage_to_owner(initial_storage: StorageUsage, sender_id: AccountId, owner_id: AccountId) {
+    let current_storage = env::storage_usage();
+    if current_storage < initial_storage {
+        Promise::new(owner_id).transfer(Balance::from(initial_storage - current_storage) * STORAGE_PRICE_PER_BYTE);
+        refund_storage(current_storage, sender_id);
+    } else {
+        refund_storage(initial_storage, sender_id);
+    }
+}
+
 pub fn ns_to_ms(ns_timestamp: u64) -> u64 {
     ns_timestamp / 1_000_000
 }
@@ -57,4 +70,27 @@ pub fn multiply_stake(stake: u128, multiplier: Option<u16>) -> u128 {
         },
         None => stake
     }
+}
+
+// @returns the absolute difference between two signed numbers
+pub fn distance(a: i128, b: i128) -> u128 {
+    if a >= b {
+        (a as u128).wrapping_sub(b as u128)
+    } else {
+        (b as u128).wrapping_sub(a as u128)
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/**
+ * @notice Calculates the commitment for a commit-reveal stake
+ * @returns hex encoded sha256 of `{account_id}:{outcome as json}:{salt}`
+ */
+pub fn calc_commitment(account_id: &AccountId, outcome: &Outcome, salt: &str) -> String {
+    let outcome_json = serde_json::to_string(outcome).expect("Failed to serialize outcome");
+    let preimage = format!("{}:{}:{}", account_id, outcome_json, salt);
+    to_hex(&env::sha256(preimage.as_bytes()))
 }
\ No newline at end of file
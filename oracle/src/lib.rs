@@ -2,7 +2,7 @@
 
 use near_sdk::{ AccountId, Balance, env, near_bindgen };
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
-use near_sdk::collections::{ Vector, LookupMap };
+use near_sdk::collections::{ Vector, LookupMap, UnorderedMap, UnorderedSet, TreeMap };
 use near_sdk::json_types::{ U64, U128 };
 
 near_sdk::setup_alloc!();
@@ -11,6 +11,7 @@ pub mod types;
 mod resolution_window;
 pub mod data_request;
 mod requestor_handler;
+pub mod target_contract_handler;
 mod fungible_token_receiver;
 pub mod callback_args;
 pub mod whitelist;
@@ -19,7 +20,12 @@ mod storage_manager;
 mod helpers;
 mod logger;
 mod upgrade;
+pub mod governance;
+pub mod pause;
 pub mod fee_config;
+pub mod recurring_request;
+pub mod account_positions;
+pub mod request_index;
 
 /// Mocks
 mod fungible_token;
@@ -29,7 +35,7 @@ pub use callback_args::*;
 use types::*;
 pub use data_request::{ DataRequest, Source };
 use storage_manager::AccountStorageBalance;
-pub use requestor_handler::Requestor;
+pub use requestor_handler::{ Requestor, RequestorCallback, RequestorOverrides };
 
 #[near_bindgen]
 #[derive(BorshSerialize, BorshDeserialize )]
@@ -38,6 +44,20 @@ pub struct Contract {
     pub configs: Vector<oracle_config::OracleConfig>,
     pub data_requests: Vector<DataRequest>,
     pub accounts: LookupMap<AccountId, AccountStorageBalance>, // storage map
+    pub recurring_requests: Vector<recurring_request::RecurringRequest>,
+    pub account_positions: LookupMap<AccountId, UnorderedMap<u64, account_positions::RequestPositions>>, // index of the stakes of each account
+    // secondary indexes of `data_requests` for `get_requests_filtered`
+    pub requests_by_requestor: LookupMap<AccountId, Vector<u64>>,
+    pub requests_by_creator: LookupMap<AccountId, Vector<u64>>,
+    pub requests_by_tag: LookupMap<String, Vector<u64>>,
+    pub request_states: TreeMap<(u8, u64), ()>,
+    pub failed_deliveries: UnorderedSet<u64>, // ids of `DataRequest`s whose outcome couldn't be delivered to the requestor
+    pub pending_config: Option<oracle_config::PendingConfig>, // config scheduled by gov that isn't active yet
+    pub pending_gov: Option<AccountId>, // account proposed as gov that still has to accept the handover
+    pub pause_state: pause::PauseState,
+    pub claim_pauses: pause::ClaimPauses,
+    pub roles: LookupMap<governance::Role, UnorderedSet<AccountId>>, // accounts that were granted a role by gov
+    pub legacy_data_requests: Option<Vector<upgrade::DataRequestV1>>, // baseline `DataRequest`s that `migrate_data_requests` still has to convert
 }
 
 impl Default for Contract {
@@ -53,6 +73,7 @@ impl Contract {
         initial_whitelist: Option<Vec<Requestor>>,
         config: oracle_config::OracleConfig,
     ) -> Self {
+        config.assert_valid();
         let mut configs = Vector::new(b"c".to_vec());
         configs.push(&config);
         logger::log_oracle_config(&config, 0);
@@ -62,6 +83,19 @@ impl Contract {
             configs,
             data_requests: Vector::new(b"dr".to_vec()),
             accounts: LookupMap::new(b"a".to_vec()),
+            recurring_requests: Vector::new(b"rr".to_vec()),
+            account_positions: LookupMap::new(b"ap".to_vec()),
+            requests_by_requestor: LookupMap::new(b"rbr".to_vec()),
+            requests_by_creator: LookupMap::new(b"rbc".to_vec()),
+            requests_by_tag: LookupMap::new(b"rbt".to_vec()),
+            request_states: TreeMap::new(b"rs".to_vec()),
+            failed_deliveries: UnorderedSet::new(b"fd".to_vec()),
+            pending_config: None,
+            pending_gov: None,
+            pause_state: pause::PauseState::default(),
+            claim_pauses: pause::ClaimPauses::default(),
+            roles: LookupMap::new(b"ro".to_vec()),
+            legacy_data_requests: None,
         }
     }
 }
@@ -12,10 +12,14 @@ use near_sdk::{
 use crate::{
     types::*,
     data_request::DataRequest,
+    recurring_request::RecurringRequest,
     resolution_window::ResolutionWindow,
     requestor_handler::Requestor,
+    pause::PauseState,
+    governance::Role,
     oracle_config::{
-        OracleConfig
+        OracleConfig,
+        PendingConfigStatus,
     },
     helpers::{
         ns_to_ms,
@@ -46,6 +50,14 @@ pub fn log_new_data_request(request: &DataRequest) {
                 "block_height": U64(env::block_index()),
                 "data_type": request.data_type,
                 "creator": request.creator,
+                "reveal_period": request.reveal_period.map(U64),
+                "tolerance": request.tolerance,
+                "slash_unrevealed_stake": request.request_config.slash_unrevealed_stake,
+                "cancelled": request.cancelled,
+                "settlement_time": request.settlement_time.map(U64),
+                "created_at": U64(request.created_at),
+                "expiry_fallback": request.expiry_fallback,
+                "target_contract": request.target_contract,
             }
         })
         .to_string()
@@ -67,6 +79,43 @@ pub fn log_update_data_request(request: &DataRequest) {
                 "finalized_outcome": request.finalized_outcome,
                 "initial_challenge_period": U64(request.initial_challenge_period),
                 "final_arbitrator_triggered": request.final_arbitrator_triggered,
+                "cancelled": request.cancelled,
+                "expired": request.expired,
+                "outcome_delivery": request.outcome_delivery,
+                "undelivered_recipients": request.undelivered_recipients,
+                "finalized_at": request.finalized_at.map(U64),
+                "claimed_correct_stake": U128(request.claimed_correct_stake),
+                "archived": request.archived,
+                "payment_balance": U128(request.payment_balance),
+                "stake_balance": U128(request.stake_balance),
+                "swept": request.swept,
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_recurring_request(recurring_request: &RecurringRequest) {
+    env::log(
+        json!({
+            "type": "recurring_requests",
+            "action": "update",
+            "cap_id": format!("rr_{}", recurring_request.id),
+            "params": {
+                "id": U64(recurring_request.id),
+                "requestor_account_id": recurring_request.requestor.account_id,
+                "global_config_id": U64(recurring_request.global_config_id),
+                "start_time": U64(recurring_request.start_time),
+                "interval": U64(recurring_request.interval),
+                "occurrences": recurring_request.occurrences,
+                "spawned": recurring_request.spawned,
+                "fee_per_occurrence": U128(recurring_request.fee_per_occurrence),
+                "escrow": U128(recurring_request.escrow),
+                "data_request_ids": recurring_request.data_request_ids.iter().map(|id| U64(*id)).collect::<Vec<U64>>(),
+                "cancelled": recurring_request.cancelled,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
             }
         })
         .to_string()
@@ -91,6 +140,17 @@ pub fn log_oracle_config(config: &OracleConfig, id: u64) {
                 "default_challenge_window_duration": config.default_challenge_window_duration,
                 "min_initial_challenge_window_duration": config.min_initial_challenge_window_duration,
                 "final_arbitrator_invoke_amount": config.final_arbitrator_invoke_amount,
+                "slash_unrevealed_stake": config.slash_unrevealed_stake,
+                "max_settlement_time_offset": config.max_settlement_time_offset,
+                "request_expiry_duration": config.request_expiry_duration,
+                "bond_escalation": config.bond_escalation,
+                "arbitration_council": config.arbitration_council,
+                "arbitration_timeout": config.arbitration_timeout,
+                "arbitration_fallback": config.arbitration_fallback,
+                "claim_window_duration": config.claim_window_duration,
+                "treasury": config.treasury,
+                "requestor_bounds": config.requestor_bounds,
+                "config_change_delay": config.config_change_delay,
                 
                 "fee": {
                     "flux_market_cap": config.fee.flux_market_cap,
@@ -107,6 +167,93 @@ pub fn log_oracle_config(config: &OracleConfig, id: u64) {
     );
 }
 
+pub fn log_pending_config(config: &OracleConfig, id: u64, activates_at: Timestamp, status: PendingConfigStatus) {
+    env::log(
+        json!({
+            "type": "pending_configs",
+            "action": "update",
+            "cap_id": format!("pc_{}", id),
+            "params": {
+                "id": format!("pc_{}", id),
+                "config_id": U64(id),
+                "gov": config.gov,
+                "final_arbitrator": config.final_arbitrator,
+                "stake_token": config.stake_token,
+                "payment_token": config.payment_token,
+                "validity_bond": config.validity_bond,
+                "activates_at": U64(ns_to_ms(activates_at)),
+                "status": status,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_gov_proposal(gov: &AccountId, proposed_gov: Option<&AccountId>) {
+    env::log(
+        json!({
+            "type": "gov_proposals",
+            "action": "update",
+            "cap_id": "gp",
+            "params": {
+                "id": "gp",
+                "gov": gov,
+                "proposed_gov": proposed_gov,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_role(account_id: &AccountId, role: Role, active: bool) {
+    env::log(
+        json!({
+            "type": "roles",
+            "action": "update",
+            "cap_id": format!("ro_{:?}_{}", role, account_id),
+            "params": {
+                "id": format!("ro_{:?}_{}", role, account_id),
+                "account_id": account_id,
+                "role": role,
+                "active": active,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_pause_state(pause_state: &PauseState) {
+    env::log(
+        json!({
+            "type": "pause_states",
+            "action": "update",
+            "cap_id": "ps",
+            "params": {
+                "id": "ps",
+                "new_requests": pause_state.new_requests,
+                "staking": pause_state.staking,
+                "unstaking": pause_state.unstaking,
+                "claiming": pause_state.claiming,
+                "finalization": pause_state.finalization,
+                "caller": env::predecessor_account_id(),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
 pub fn log_resolution_window(window: &ResolutionWindow) {
     env::log(
         json!({
@@ -121,6 +268,9 @@ pub fn log_resolution_window(window: &ResolutionWindow) {
                 "end_time": U64(window.end_time),
                 "bond_size": U128(window.bond_size),
                 "bonded_outcome": window.bonded_outcome,
+                "commit_end_time": window.commit_end_time.map(U64),
+                "total_committed": U128(window.total_committed),
+                "total_revealed": U128(window.total_revealed),
 
                 "date": U64(ns_to_ms(env::block_timestamp())),
                 "block_height": U64(env::block_index()),
@@ -194,6 +344,7 @@ pub fn log_claim(
     user_correct_stake: u128, 
     stake_profit: u128,
     fee_profit: u128,
+    refunded_stake: u128,
 ) {
     env::log(
         json!({
@@ -224,6 +375,48 @@ pub fn log_claim(
                 "user_correct_stake": U128(user_correct_stake),
                 "payout": U128(stake_profit),
                 "fee_profit": U128(fee_profit),
+                "refunded_stake": U128(refunded_stake),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_arbitration_vote(data_request_id: u64, member: &AccountId, outcome: &Outcome) {
+    env::log(
+        json!({
+            "type": "arbitration_votes",
+            "action": "update",
+            "cap_id": format!("av_{}_{}", data_request_id, member),
+            "params": {
+                "id": format!("av_{}_{}", data_request_id, member),
+                "data_request_id": U64(data_request_id),
+                "member": member,
+                "outcome": outcome,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes()
+    );
+}
+
+pub fn log_sweep(data_request_id: u64, treasury: &AccountId, payment_amount: Balance, stake_amount: Balance) {
+    env::log(
+        json!({
+            "type": "sweeps",
+            "action": "add",
+            "cap_id": format!("sw_{}", data_request_id),
+            "params": {
+                "id": format!("sw_{}", data_request_id),
+                "data_request_id": U64(data_request_id),
+                "treasury": treasury,
+                "payment_amount": U128(payment_amount),
+                "stake_amount": U128(stake_amount),
                 "date": U64(ns_to_ms(env::block_timestamp())),
                 "block_height": U64(env::block_index()),
             }
@@ -245,6 +438,7 @@ pub fn log_whitelist(requestor: &Requestor, active: bool) {
                 "account_id": requestor.account_id,
                 "stake_multiplier": requestor.stake_multiplier,
                 "code_base_url": requestor.code_base_url,
+                "overrides": requestor.overrides,
                 "active": active,
                 "date": U64(ns_to_ms(env::block_timestamp())),
                 "block_height": U64(env::block_index()),
@@ -259,6 +453,8 @@ pub fn log_whitelist(requestor: &Requestor, active: bool) {
 pub enum TransactionType {
     Stake,
     Unstake,
+    Commit,
+    Reveal,
 }
 
 pub fn log_transaction(
@@ -324,3 +520,37 @@ pub fn log_unstake_transaction(
         Some(outcome_to_id(outcome))
     );
 }
+
+
+pub fn log_commit_transaction(
+    account_id: &AccountId, 
+    window: &ResolutionWindow, 
+    amount_in: Balance
+) {
+    log_transaction(
+        TransactionType::Commit, 
+        account_id, 
+        window.dr_id, 
+        Some(window.round), 
+        amount_in,
+        0, 
+        None
+    );
+}
+
+pub fn log_reveal_transaction(
+    account_id: &AccountId, 
+    window: &ResolutionWindow, 
+    amount_revealed: Balance,
+    outcome: &Outcome
+) {
+    log_transaction(
+        TransactionType::Reveal, 
+        account_id, 
+        window.dr_id, 
+        Some(window.round), 
+        amount_revealed,
+        0, 
+        Some(outcome_to_id(outcome))
+    );
+}
\ No newline at end of file
@@ -3,6 +3,165 @@ use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::serde::{ Serialize, Deserialize };
 use near_sdk::{ AccountId };
 use fee_config::FeeConfig;
+use crate::data_request::PERCENTAGE_DIVISOR;
+use crate::requestor_handler::RequestorOverrides;
+use crate::callback_args::{ MIN_OUTCOMES, MIN_PERIOD_MULTIPLIER };
+
+/**
+ * @notice How the bond grows from one `ResolutionWindow` to the next, multipliers are denominated in `PERCENTAGE_DIVISOR`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BondEscalation {
+    Multiplier(u32), // every round's bond is the previous bond times the multiplier
+    Linear(U128), // every round's bond is the previous bond plus a fixed increment
+    Schedule(Vec<u32>), // multiplier per round, rounds past the end of the schedule use the last multiplier
+}
+
+impl BondEscalation {
+    // @returns the bond of `round` given the bond of the round before it, for round 0 that's the base resolution bond
+    pub fn next_bond(&self, prev_bond: Balance, round: u16) -> Balance {
+        match self {
+            BondEscalation::Multiplier(multiplier) => helpers::calc_product(prev_bond, *multiplier as u128, PERCENTAGE_DIVISOR as u128),
+            BondEscalation::Linear(increment) => prev_bond + u128::from(*increment),
+            BondEscalation::Schedule(schedule) => {
+                let multiplier = schedule[std::cmp::min(round as usize, schedule.len() - 1)];
+                helpers::calc_product(prev_bond, multiplier as u128, PERCENTAGE_DIVISOR as u128)
+            }
+        }
+    }
+
+    // @notice Bonds should never decrease between rounds
+    pub fn assert_valid(&self) {
+        match self {
+            BondEscalation::Multiplier(multiplier) => assert!(*multiplier >= PERCENTAGE_DIVISOR as u32, "Bond multiplier should be at least {}", PERCENTAGE_DIVISOR),
+            BondEscalation::Linear(increment) => assert!(u128::from(*increment) > 0, "Bond increment should be greater than 0"),
+            BondEscalation::Schedule(schedule) => {
+                assert!(!schedule.is_empty(), "Bond schedule can't be empty");
+                assert!(
+                    schedule.iter().all(|multiplier| *multiplier >= PERCENTAGE_DIVISOR as u32),
+                    "Bond multipliers in the schedule should be at least {}",
+                    PERCENTAGE_DIVISOR
+                );
+            }
+        }
+    }
+}
+
+/**
+ * @notice Replaces the single `final_arbitrator` with a set of members, a request is finalized once `threshold` members voted for the same outcome
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArbitrationCouncil {
+    pub members: Vec<AccountId>,
+    pub threshold: u16,
+}
+
+impl ArbitrationCouncil {
+    pub fn assert_valid(&self) {
+        assert!(
+            self.threshold > 0 && self.threshold as usize <= self.members.len(),
+            "Arbitration council threshold should be between 1 and the amount of members"
+        );
+        let mut members = self.members.clone();
+        members.sort();
+        members.dedup();
+        assert_eq!(members.len(), self.members.len(), "Arbitration council members should be unique");
+    }
+
+    pub fn is_member(&self, account_id: &AccountId) -> bool {
+        self.members.contains(account_id)
+    }
+}
+
+/**
+ * @notice Outcome a `DataRequest` is finalized with when the final arbitrator doesn't act before the `arbitration_timeout`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ArbitrationFallback {
+    LastBondedOutcome,
+    Invalid,
+}
+
+/**
+ * @notice Range within which gov allows the `RequestorOverrides` of whitelisted requestors
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestorBounds {
+    pub min_validity_bond: U128,
+    pub max_validity_bond: U128,
+    pub min_challenge_window_duration: WrappedTimestamp, // bounds both the minimum initial and the default challenge window duration
+    pub max_challenge_window_duration: WrappedTimestamp,
+    pub max_outcomes: u8,
+    pub min_final_arbitrator_invoke_amount: U128,
+    pub max_final_arbitrator_invoke_amount: U128,
+}
+
+impl RequestorBounds {
+    pub fn assert_valid(&self) {
+        assert!(self.min_validity_bond.0 <= self.max_validity_bond.0, "Requestor bounds minimum validity bond exceeds the maximum");
+        assert!(self.min_challenge_window_duration.0 <= self.max_challenge_window_duration.0, "Requestor bounds minimum challenge window duration exceeds the maximum");
+        assert!(self.max_outcomes > 0, "Requestor bounds max outcomes should be greater than 0");
+        assert!(self.min_final_arbitrator_invoke_amount.0 <= self.max_final_arbitrator_invoke_amount.0, "Requestor bounds minimum final arbitrator invoke amount exceeds the maximum");
+    }
+
+    pub fn assert_within(&self, overrides: &RequestorOverrides) {
+        if let Some(validity_bond) = overrides.validity_bond {
+            assert!(
+                validity_bond.0 >= self.min_validity_bond.0 && validity_bond.0 <= self.max_validity_bond.0,
+                "Validity bond override should be between {} and {}",
+                self.min_validity_bond.0,
+                self.max_validity_bond.0
+            );
+        }
+        for duration in [overrides.min_initial_challenge_window_duration, overrides.default_challenge_window_duration].iter().flatten() {
+            assert!(
+                duration.0 >= self.min_challenge_window_duration.0 && duration.0 <= self.max_challenge_window_duration.0,
+                "Challenge window duration override should be between {} and {}",
+                self.min_challenge_window_duration.0,
+                self.max_challenge_window_duration.0
+            );
+        }
+        if let Some(max_outcomes) = overrides.max_outcomes {
+            assert!(max_outcomes <= self.max_outcomes, "Max outcomes override can't exceed {}", self.max_outcomes);
+        }
+        if let Some(invoke_amount) = overrides.final_arbitrator_invoke_amount {
+            assert!(
+                invoke_amount.0 >= self.min_final_arbitrator_invoke_amount.0 && invoke_amount.0 <= self.max_final_arbitrator_invoke_amount.0,
+                "Final arbitrator invoke amount override should be between {} and {}",
+                self.min_final_arbitrator_invoke_amount.0,
+                self.max_final_arbitrator_invoke_amount.0
+            );
+        }
+    }
+}
+
+/**
+ * @notice A config proposed by gov that can be activated once `activates_at` has passed
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingConfig {
+    pub config: OracleConfig,
+    pub activates_at: WrappedTimestamp,
+    pub scheduled_by: AccountId, // paid for the storage of the pending config, it's refunded to them once activated or cancelled
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PendingConfigStatus {
+    Scheduled,
+    Activated,
+    Cancelled,
+}
+
+// @returns `value` limited to the range of `min` up to and including `max`
+fn bound<T: Ord>(value: T, min: T, max: T) -> T {
+    std::cmp::max(min, std::cmp::min(max, value))
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -16,29 +175,175 @@ pub struct OracleConfig {
     pub default_challenge_window_duration: WrappedTimestamp,
     pub min_initial_challenge_window_duration: WrappedTimestamp,
     pub final_arbitrator_invoke_amount: U128, // Amount of tokens that, when bonded in a single `ResolutionWindow`, should trigger the final arbitrator
+    pub slash_unrevealed_stake: bool, // Whether stake that is committed but never revealed in commit-reveal requests goes to the correct stakers (true) or is refunded (false)
+    pub max_settlement_time_offset: WrappedTimestamp, // Maximum time between the creation of a `DataRequest` and its `settlement_time`
+    pub request_expiry_duration: WrappedTimestamp, // Time after creation (or `settlement_time`) after which a `DataRequest` without a bonded outcome can be expired
+    pub bond_escalation: BondEscalation,
+    pub arbitration_council: Option<ArbitrationCouncil>, // When set the council arbitrates instead of `final_arbitrator`
+    pub arbitration_timeout: WrappedTimestamp, // Time after the final arbitrator is triggered after which anyone can finalize with the `arbitration_fallback`, 0 disables the timeout
+    pub arbitration_fallback: ArbitrationFallback,
+    pub claim_window_duration: WrappedTimestamp, // Time after finalization after which a `DataRequest` can be archived even if not all stake is claimed
+    pub treasury: AccountId, // Receives the funds of a `DataRequest` that are left over after its claim window
+    pub requestor_bounds: Option<RequestorBounds>, // When set whitelisted requestors can override request parameters within these bounds
+    pub config_change_delay: WrappedTimestamp, // Time between gov proposing a new config and it becoming active, 0 makes changes take effect immediately
     pub fee: FeeConfig,
 }
 
+impl OracleConfig {
+    /**
+     * @notice Shared by `new`, `set_config` and `update_fee_config` so every config that can become active passes the same checks
+     */
+    pub fn assert_valid(&self) {
+        let default_challenge_window_duration: u64 = self.default_challenge_window_duration.into();
+        assert!(default_challenge_window_duration > 0, "Default challenge window duration should be greater than 0");
+        let max_initial_challenge_window_duration = default_challenge_window_duration.checked_mul(MIN_PERIOD_MULTIPLIER);
+        assert!(max_initial_challenge_window_duration.is_some(), "Default challenge window duration is too large");
+        let max_initial_challenge_window_duration = max_initial_challenge_window_duration.unwrap();
+        assert!(
+            u64::from(self.min_initial_challenge_window_duration) <= max_initial_challenge_window_duration,
+            "Minimum initial challenge window duration can't exceed {}",
+            max_initial_challenge_window_duration
+        );
+        assert!(self.max_outcomes >= MIN_OUTCOMES, "Max outcomes should be at least {}", MIN_OUTCOMES);
+        assert!(u64::from(self.claim_window_duration) > 0, "Claim window duration should be greater than 0");
+        assert!(env::is_valid_account_id(self.gov.as_bytes()), "Invalid gov account id: {}", self.gov);
+        assert!(env::is_valid_account_id(self.final_arbitrator.as_bytes()), "Invalid final arbitrator account id: {}", self.final_arbitrator);
+        assert!(env::is_valid_account_id(self.treasury.as_bytes()), "Invalid treasury account id: {}", self.treasury);
+        self.bond_escalation.assert_valid();
+        if let Some(council) = &self.arbitration_council {
+            council.assert_valid();
+        }
+        if let Some(bounds) = &self.requestor_bounds {
+            bounds.assert_valid();
+        }
+        self.fee.assert_valid();
+    }
+
+    /**
+     * @notice Applies the overrides of `requestor`, overrides are limited to the current `requestor_bounds` and ignored without them
+     * @returns the config `DataRequest`s of `requestor` are validated and created with
+     */
+    pub fn for_requestor(&self, requestor: &Requestor) -> OracleConfig {
+        let mut config = self.clone();
+        let (bounds, overrides) = match (&self.requestor_bounds, &requestor.overrides) {
+            (Some(bounds), Some(overrides)) => (bounds, overrides),
+            _ => return config
+        };
+
+        if let Some(validity_bond) = overrides.validity_bond {
+            config.validity_bond = U128(bound(validity_bond.0, bounds.min_validity_bond.0, bounds.max_validity_bond.0));
+        }
+        if let Some(duration) = overrides.min_initial_challenge_window_duration {
+            config.min_initial_challenge_window_duration = U64(bound(duration.0, bounds.min_challenge_window_duration.0, bounds.max_challenge_window_duration.0));
+        }
+        if let Some(duration) = overrides.default_challenge_window_duration {
+            config.default_challenge_window_duration = U64(bound(duration.0, bounds.min_challenge_window_duration.0, bounds.max_challenge_window_duration.0));
+        }
+        if let Some(max_outcomes) = overrides.max_outcomes {
+            config.max_outcomes = std::cmp::min(max_outcomes, bounds.max_outcomes);
+        }
+        if let Some(invoke_amount) = overrides.final_arbitrator_invoke_amount {
+            config.final_arbitrator_invoke_amount = U128(bound(invoke_amount.0, bounds.min_final_arbitrator_invoke_amount.0, bounds.max_final_arbitrator_invoke_amount.0));
+        }
+
+        config
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     pub fn get_config(&self) -> OracleConfig {
         self.configs.iter().last().unwrap()
     }
 
+    // @returns the config with `id`, this is the `global_config_id` `DataRequest`s are pinned to
+    pub fn get_config_by_id(&self, id: U64) -> Option<OracleConfig> {
+        self.configs.get(id.into())
+    }
+
+    pub fn get_configs(&self, from_index: U64, limit: U64) -> Vec<OracleConfig> {
+        let i: u64 = from_index.into();
+        (i..std::cmp::min(i.saturating_add(limit.into()), self.configs.len()))
+            .map(|index| self.configs.get(index).unwrap())
+            .collect()
+    }
+
+    /**
+     * @notice Replaces the current config, when the current config has a `config_change_delay` the new config is
+     * only scheduled and has to be activated through `activate_pending_config` once the delay has passed
+     */
     #[payable]
     pub fn set_config(&mut self, new_config: OracleConfig) {
-        self.assert_gov();
+        self.assert_role(governance::Role::ConfigAdmin);
                 
         let initial_storage = env::storage_usage();
+        assert_eq!(new_config.gov, self.get_config().gov, "Governance can only be changed through `propose_gov`");
+        new_config.assert_valid();
 
-        self.configs.push(&new_config);
+        let delay: u64 = self.get_config().config_change_delay.into();
+        if delay == 0 {
+            self.configs.push(&new_config);
+            logger::log_oracle_config(&new_config, self.configs.len() - 1);
+        } else {
+            self.schedule_config(new_config, delay);
+        }
 
-        logger::log_oracle_config(&new_config, self.configs.len() - 1);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
     }
+
+    /**
+     * @notice Makes the pending config the current config, callable by anyone once its delay has passed. The freed storage
+     * of the pending config is refunded to the account that scheduled it
+     */
+    #[payable]
+    pub fn activate_pending_config(&mut self) {
+        let initial_storage = env::storage_usage();
+
+        let pending = self.pending_config.take().expect("There is no pending config");
+        assert!(
+            env::block_timestamp() >= u64::from(pending.activates_at),
+            "Pending config can't be activated before {}",
+            u64::from(pending.activates_at)
+        );
+
+        self.configs.push(&pending.config);
+
+        logger::log_pending_config(&pending.config, self.configs.len() - 1, pending.activates_at.into(), PendingConfigStatus::Activated);
+        logger::log_oracle_config(&pending.config, self.configs.len() - 1);
+        helpers::refund_storage_to_owner(initial_storage, env::predecessor_account_id(), pending.scheduled_by);
+    }
+
+    #[payable]
+    pub fn cancel_pending_config(&mut self) {
+        self.assert_role(governance::Role::ConfigAdmin);
+
+        let initial_storage = env::storage_usage();
+
+        let pending = self.pending_config.take().expect("There is no pending config");
+
+        logger::log_pending_config(&pending.config, self.configs.len(), pending.activates_at.into(), PendingConfigStatus::Cancelled);
+        helpers::refund_storage_to_owner(initial_storage, env::predecessor_account_id(), pending.scheduled_by);
+    }
+
+    pub fn get_pending_config(&self) -> Option<PendingConfig> {
+        self.pending_config.clone()
+    }
 }
 
 impl Contract {
+    // @notice queues `config` to become activatable after `delay`, only one change can be pending at a time
+    pub fn schedule_config(&mut self, config: OracleConfig, delay: Duration) {
+        assert!(self.pending_config.is_none(), "There is already a pending config, cancel it first");
+
+        let activates_at = env::block_timestamp() + delay;
+        logger::log_pending_config(&config, self.configs.len(), activates_at, PendingConfigStatus::Scheduled);
+        self.pending_config = Some(PendingConfig {
+            config,
+            activates_at: U64(activates_at),
+            scheduled_by: env::predecessor_account_id(),
+        });
+    }
+
     pub fn assert_sender(&self, expected_sender: &AccountId) {
         assert_eq!(&env::predecessor_account_id(), expected_sender, "This function can only be called by {}", expected_sender);
     }
@@ -61,6 +366,10 @@ mod mock_token_basic_tests {
         "bob.near".to_string()
     }
 
+    fn carol() -> AccountId {
+        "carol.near".to_string()
+    }
+
     fn token() -> AccountId {
         "token.near".to_string()
     }
@@ -80,6 +389,17 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
@@ -111,10 +431,29 @@ mod mock_token_basic_tests {
 
     #[test]
     fn set_config_from_gov() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_config(arbitrated_config(bob()));
+        assert_eq!(contract.get_config().final_arbitrator, bob());
+    }
+
+    #[test]
+    fn set_config_from_config_admin() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), governance::Role::ConfigAdmin);
+
+        testing_env!(get_context(alice()));
+        contract.set_config(arbitrated_config(bob()));
+        assert_eq!(contract.get_config().final_arbitrator, bob());
+    }
+
+    #[test]
+    #[should_panic(expected = "Governance can only be changed through `propose_gov`")]
+    fn fail_set_config_changing_gov() {
         testing_env!(get_context(gov()));
         let mut contract = Contract::new(None, config(gov()));
         contract.set_config(config(alice()));
-        assert_eq!(contract.get_config().gov, alice());
     }
 
     #[test]
@@ -124,4 +463,244 @@ mod mock_token_basic_tests {
         let mut contract = Contract::new(None, config(gov()));
         contract.set_config(config(alice()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "Bond multiplier should be at least 10000")]
+    fn fail_set_config_decreasing_bond() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.bond_escalation = BondEscalation::Multiplier(9_999);
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Arbitration council threshold should be between 1 and the amount of members")]
+    fn fail_set_config_council_threshold() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.arbitration_council = Some(ArbitrationCouncil {
+            members: vec![alice(), bob()],
+            threshold: 3,
+        });
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid treasury account id: Treasury")]
+    fn fail_set_config_invalid_treasury() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.treasury = "Treasury".to_string();
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid gov account id: Gov")]
+    fn fail_new_invalid_gov() {
+        testing_env!(get_context(gov()));
+        Contract::new(None, config("Gov".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid final arbitrator account id: Arbitrator")]
+    fn fail_set_config_invalid_final_arbitrator() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.final_arbitrator = "Arbitrator".to_string();
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim window duration should be greater than 0")]
+    fn fail_set_config_zero_claim_window() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.claim_window_duration = U64(0);
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Default challenge window duration is too large")]
+    fn fail_set_config_overflowing_challenge_window() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.default_challenge_window_duration = U64(u64::MAX);
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Default challenge window duration should be greater than 0")]
+    fn fail_set_config_zero_challenge_window() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.default_challenge_window_duration = U64(0);
+        new_config.min_initial_challenge_window_duration = U64(0);
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum initial challenge window duration can't exceed 3000")]
+    fn fail_set_config_min_challenge_window_exceeds_max() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.min_initial_challenge_window_duration = U64(3001);
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Max outcomes should be at least 2")]
+    fn fail_set_config_max_outcomes() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.max_outcomes = 1;
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeds max resolution fee percentage")]
+    fn fail_set_config_fee() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        let mut new_config = config(gov());
+        new_config.fee.resolution_fee_percentage = 5001;
+        contract.set_config(new_config);
+    }
+
+    #[test]
+    #[should_panic(expected = "TVS must be lower than market cap")]
+    fn fail_new_invalid_config() {
+        testing_env!(get_context(gov()));
+        let mut c = config(gov());
+        c.fee.total_value_staked = c.fee.flux_market_cap;
+        Contract::new(None, c);
+    }
+
+    #[test]
+    fn config_history() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_config(arbitrated_config(bob()));
+        contract.set_config(arbitrated_config(carol()));
+
+        assert_eq!(contract.get_config_by_id(U64(1)).unwrap().final_arbitrator, bob());
+        assert!(contract.get_config_by_id(U64(3)).is_none());
+
+        let arbitrators: Vec<AccountId> = contract.get_configs(U64(1), U64(5)).into_iter().map(|c| c.final_arbitrator).collect();
+        assert_eq!(arbitrators, vec![bob(), carol()]);
+        assert_eq!(contract.get_configs(U64(0), U64(1)).len(), 1);
+    }
+
+    #[test]
+    fn bond_escalation_curves() {
+        assert_eq!(BondEscalation::Multiplier(15_000).next_bond(200, 1), 300);
+        assert_eq!(BondEscalation::Linear(U128(50)).next_bond(200, 1), 250);
+
+        let schedule = BondEscalation::Schedule(vec![20_000, 30_000]);
+        assert_eq!(schedule.next_bond(100, 0), 200);
+        assert_eq!(schedule.next_bond(200, 1), 600);
+        assert_eq!(schedule.next_bond(600, 5), 1800);
+    }
+
+    fn arbitrated_config(final_arbitrator: AccountId) -> oracle_config::OracleConfig {
+        let mut c = config(gov());
+        c.final_arbitrator = final_arbitrator;
+        c
+    }
+
+    fn delayed_contract() -> Contract {
+        let mut c = config(gov());
+        c.config_change_delay = U64(1000);
+        Contract::new(None, c)
+    }
+
+    fn context_at(predecessor_account_id: AccountId, block_timestamp: u64) -> VMContext {
+        let mut context = get_context(predecessor_account_id);
+        context.block_timestamp = block_timestamp;
+        context
+    }
+
+    // @returns whether a `pending_configs` event with `status` was logged since the context was set
+    fn pending_config_logged(status: &str) -> bool {
+        near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains("\"type\":\"pending_configs\"") && log.contains(&format!("\"status\":\"{}\"", status)))
+    }
+
+    #[test]
+    fn set_config_delayed() {
+        testing_env!(context_at(gov(), 100));
+        let mut contract = delayed_contract();
+        contract.set_config(arbitrated_config(bob()));
+        assert_eq!(contract.get_config().final_arbitrator, alice());
+        assert!(pending_config_logged("Scheduled"));
+
+        let pending = contract.get_pending_config().unwrap();
+        assert_eq!(pending.config.final_arbitrator, bob());
+        assert_eq!(pending.activates_at, U64(1100));
+        assert_eq!(pending.scheduled_by, gov());
+
+        testing_env!(context_at(bob(), 1100));
+        contract.activate_pending_config();
+        assert_eq!(contract.get_config().final_arbitrator, bob());
+        assert!(contract.get_pending_config().is_none());
+        assert!(pending_config_logged("Activated"));
+        assert!(!pending_config_logged("Cancelled"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pending config can't be activated before 1100")]
+    fn activate_pending_config_too_early() {
+        testing_env!(context_at(gov(), 100));
+        let mut contract = delayed_contract();
+        contract.set_config(arbitrated_config(bob()));
+
+        testing_env!(context_at(bob(), 1099));
+        contract.activate_pending_config();
+    }
+
+    #[test]
+    #[should_panic(expected = "There is already a pending config, cancel it first")]
+    fn set_config_while_pending() {
+        testing_env!(get_context(gov()));
+        let mut contract = delayed_contract();
+        contract.set_config(arbitrated_config(bob()));
+        contract.set_config(arbitrated_config(carol()));
+    }
+
+    #[test]
+    #[should_panic(expected = "There is no pending config")]
+    fn cancel_pending_config() {
+        testing_env!(get_context(gov()));
+        let mut contract = delayed_contract();
+        contract.set_config(arbitrated_config(bob()));
+
+        testing_env!(get_context(gov()));
+        contract.cancel_pending_config();
+        assert!(contract.get_pending_config().is_none());
+        assert!(pending_config_logged("Cancelled"));
+        assert!(!pending_config_logged("Activated"));
+
+        testing_env!(context_at(bob(), 1000));
+        contract.activate_pending_config();
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn fail_cancel_pending_config_from_user() {
+        testing_env!(get_context(gov()));
+        let mut contract = delayed_contract();
+        contract.set_config(arbitrated_config(bob()));
+
+        testing_env!(get_context(alice()));
+        contract.cancel_pending_config();
+    }
+}
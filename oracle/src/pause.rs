@@ -0,0 +1,248 @@
+use crate::*;
+use near_sdk::serde::{ Deserialize, Serialize };
+
+/**
+ * @notice Operations that can be halted independently during an incident
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct PauseState {
+    pub new_requests: bool, // `dr_new`, `dr_new_recurring` and `dr_spawn_due`
+    pub staking: bool, // `dr_stake`, `dr_commit` and `dr_reveal`
+    pub unstaking: bool, // `dr_unstake`
+    pub claiming: bool, // `dr_claim`, `dr_claim_many`, `dr_sweep` and `dr_archive`, claim deadlines are extended while paused
+    pub finalization: bool, // `dr_finalize`, `dr_expire` and the final arbitrator methods
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    NewRequest,
+    Stake,
+    Unstake,
+    Claim,
+    Finalize,
+}
+
+/**
+ * @notice Periods claiming was paused, claim deadlines are pushed back by the part of a pause that fell inside the claim window
+ */
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ClaimPauses {
+    pub paused_since: Option<Timestamp>, // start of the pause that's still ongoing
+    pub periods: Vector<(Timestamp, Timestamp)>, // start and end of every ended pause, oldest first
+}
+
+impl Default for ClaimPauses {
+    fn default() -> Self {
+        Self {
+            paused_since: None,
+            periods: Vector::new(b"cps".to_vec()),
+        }
+    }
+}
+
+impl ClaimPauses {
+    pub fn set_paused(&mut self, paused: bool) {
+        match (self.paused_since, paused) {
+            (None, true) => self.paused_since = Some(env::block_timestamp()),
+            (Some(paused_since), false) => {
+                self.periods.push(&(paused_since, env::block_timestamp()));
+                self.paused_since = None;
+            },
+            _ => {}
+        }
+    }
+
+    /**
+     * @notice Claim windows only run while claiming isn't paused, an ongoing pause keeps moving the deadline until it ends
+     * @returns the end of a claim window of `duration` that opened at `start`
+     */
+    pub fn claim_deadline(&self, start: Timestamp, duration: Duration) -> Timestamp {
+        let ongoing = self.paused_since.map(|paused_since| (paused_since, env::block_timestamp()));
+        let mut deadline = start.saturating_add(duration);
+        for (paused_at, unpaused_at) in self.periods.iter().chain(ongoing) {
+            if unpaused_at <= start {
+                continue;
+            }
+            if paused_at >= deadline {
+                break;
+            }
+            deadline = deadline.saturating_add(unpaused_at - std::cmp::max(paused_at, start));
+        }
+        deadline
+    }
+}
+
+impl PauseState {
+    pub fn is_paused(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::NewRequest => self.new_requests,
+            Operation::Stake => self.staking,
+            Operation::Unstake => self.unstaking,
+            Operation::Claim => self.claiming,
+            Operation::Finalize => self.finalization,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // @notice replaces the whole `PauseState`, callable by gov or accounts with the `Pauser` role who pay for the recorded claim pauses
+    #[payable]
+    pub fn set_pause_state(&mut self, pause_state: PauseState) {
+        self.assert_role(governance::Role::Pauser);
+
+        let initial_storage = env::storage_usage();
+        self.claim_pauses.set_paused(pause_state.claiming);
+        self.pause_state = pause_state;
+
+        logger::log_pause_state(&self.pause_state);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    pub fn get_pause_state(&self) -> PauseState {
+        self.pause_state.clone()
+    }
+}
+
+impl Contract {
+    pub fn assert_not_paused(&self, operation: Operation) {
+        assert!(!self.pause_state.is_paused(operation), "{:?} is paused", operation);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use near_sdk::{ MockedBlockchain };
+    use near_sdk::{ testing_env, VMContext };
+    use fee_config::FeeConfig;
+    use super::*;
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn bob() -> AccountId {
+        "bob.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn gov() -> AccountId {
+        "gov.near".to_string()
+    }
+
+    fn config(gov: AccountId) -> oracle_config::OracleConfig {
+        oracle_config::OracleConfig {
+            gov,
+            final_arbitrator: alice(),
+            payment_token: token(),
+            stake_token: token(),
+            validity_bond: U128(0),
+            max_outcomes: 8,
+            default_challenge_window_duration: U64(1000),
+            min_initial_challenge_window_duration: U64(1000),
+            final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
+            fee: FeeConfig {
+                flux_market_cap: U128(50000),
+                total_value_staked: U128(10000),
+                resolution_fee_percentage: 5000, // 5%
+            }
+        }
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: token(),
+            signer_account_id: bob(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 1000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 19000000000000000000000,
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    fn paused_claims() -> PauseState {
+        PauseState {
+            claiming: true,
+            ..PauseState::default()
+        }
+    }
+
+    #[test]
+    fn pause_from_gov() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_pause_state(paused_claims());
+        assert_eq!(contract.get_pause_state(), paused_claims());
+        contract.assert_not_paused(Operation::Stake);
+    }
+
+    #[test]
+    fn pause_from_pauser() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), governance::Role::Pauser);
+
+        testing_env!(get_context(alice()));
+        contract.set_pause_state(paused_claims());
+        contract.set_pause_state(PauseState::default());
+        assert_eq!(contract.get_pause_state(), PauseState::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn pause_from_user() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.grant_role(alice(), governance::Role::Pauser);
+
+        testing_env!(get_context(bob()));
+        contract.set_pause_state(paused_claims());
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim is paused")]
+    fn paused_operation() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_pause_state(paused_claims());
+        contract.assert_not_paused(Operation::Claim);
+    }
+
+    #[test]
+    #[should_panic(expected = "The required attached deposit is")]
+    fn pause_claims_without_storage_deposit() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_pause_state(paused_claims());
+
+        let mut ct = get_context(gov());
+        ct.attached_deposit = 0;
+        testing_env!(ct);
+        contract.set_pause_state(PauseState::default());
+    }
+}
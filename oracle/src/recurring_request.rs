@@ -0,0 +1,536 @@
+use crate::*;
+
+use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
+use near_sdk::serde::{ Serialize, Deserialize };
+use near_sdk::{ AccountId, Promise, PromiseOrValue };
+
+use crate::types::*;
+use crate::logger;
+use crate::fungible_token::{ fungible_token_transfer };
+
+const MAX_OCCURRENCES: u16 = 1000;
+const MAX_SPAWNS_PER_CALL: u16 = 10;
+
+#[derive(Serialize, Deserialize)]
+pub struct NewRecurringRequestArgs {
+    pub template: NewDataRequestArgs, // `settlement_time` is overwritten with the due time of each occurrence
+    pub start_time: WrappedTimestamp, // Due time of the first occurrence
+    pub interval: WrappedTimestamp,
+    pub occurrences: u16,
+    pub fee_per_occurrence: WrappedBalance,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RecurringRequest {
+    pub id: u64,
+    pub requestor: Requestor,
+    pub global_config_id: u64, // Config all occurrences are created with
+    pub template: NewDataRequestArgs,
+    pub start_time: Timestamp,
+    pub interval: Duration,
+    pub occurrences: u16,
+    pub spawned: u16,
+    pub fee_per_occurrence: Balance,
+    pub validity_bond: Balance,
+    pub escrow: Balance, // Prepaid fees and validity bonds for the occurrences that haven't been spawned yet
+    pub data_request_ids: Vec<u64>,
+    pub cancelled: bool, // the escrow of the unspawned occurrences is refunded to the creator, nothing is spawned anymore
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecurringRequestSummary {
+    pub id: U64,
+    pub requestor: Requestor,
+    pub global_config_id: U64,
+    pub template: NewDataRequestArgs,
+    pub start_time: WrappedTimestamp,
+    pub interval: WrappedTimestamp,
+    pub occurrences: u16,
+    pub spawned: u16,
+    pub fee_per_occurrence: WrappedBalance,
+    pub validity_bond: WrappedBalance,
+    pub escrow: WrappedBalance,
+    pub next_due_time: Option<WrappedTimestamp>,
+    pub data_request_ids: Vec<U64>,
+    pub cancelled: bool,
+}
+
+impl RecurringRequest {
+    pub fn cost_per_occurrence(&self) -> Balance {
+        self.fee_per_occurrence + self.validity_bond
+    }
+
+    // @returns the due time of the next occurrence, `None` if all occurrences were spawned or the recurring request is cancelled
+    pub fn next_due_time(&self) -> Option<Timestamp> {
+        if self.cancelled || self.spawned >= self.occurrences {
+            return None;
+        }
+        Some(self.start_time + self.interval * self.spawned as u64)
+    }
+
+    pub fn is_due(&self) -> bool {
+        match self.next_due_time() {
+            Some(due_time) => env::block_timestamp() >= due_time,
+            None => false
+        }
+    }
+
+    // @returns the arguments for the next occurrence and takes its fee and validity bond out of the escrow
+    pub fn next_occurrence(&mut self) -> NewDataRequestArgs {
+        let due_time = self.next_due_time().expect("All occurrences have been spawned");
+        let mut args = self.template.clone();
+        args.settlement_time = Some(U64(due_time));
+
+        self.escrow -= self.cost_per_occurrence();
+        self.spawned += 1;
+
+        args
+    }
+
+    pub fn summarize(&self) -> RecurringRequestSummary {
+        RecurringRequestSummary {
+            id: U64(self.id),
+            requestor: self.requestor.clone(),
+            global_config_id: U64(self.global_config_id),
+            template: self.template.clone(),
+            start_time: U64(self.start_time),
+            interval: U64(self.interval),
+            occurrences: self.occurrences,
+            spawned: self.spawned,
+            fee_per_occurrence: U128(self.fee_per_occurrence),
+            validity_bond: U128(self.validity_bond),
+            escrow: U128(self.escrow),
+            next_due_time: self.next_due_time().map(U64),
+            data_request_ids: self.data_request_ids.iter().map(|id| U64(*id)).collect(),
+            cancelled: self.cancelled,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice creates a recurring request template, `amount` should cover the fee and validity bond of every occurrence
+     * @returns amount of tokens that weren't escrowed
+     */
+    pub fn dr_new_recurring(&mut self, sender: AccountId, amount: Balance, payload: NewRecurringRequestArgs) -> PromiseOrValue<WrappedBalance> {
+        self.assert_not_paused(pause::Operation::NewRequest);
+        self.assert_whitelisted(sender.to_string());
+        let requestor = self.whitelist.whitelist_get_expect(&sender);
+        let config = self.get_config().for_requestor(&requestor);
+        let validity_bond: u128 = config.validity_bond.into();
+        self.assert_sender(&config.payment_token);
+
+        let interval: u64 = payload.interval.into();
+        assert!(interval > 0, "Interval should be greater than 0");
+        assert!(
+            payload.occurrences > 0 && payload.occurrences <= MAX_OCCURRENCES,
+            "Occurrences should be between 1 and {}",
+            MAX_OCCURRENCES
+        );
+
+        // Validate the template the way the first and last occurrence will be created, the last occurrence has the
+        // settlement time furthest away from now
+        let mut first_occurrence = payload.template.clone();
+        first_occurrence.settlement_time = Some(payload.start_time);
+        self.dr_validate(&first_occurrence, &requestor);
+
+        let last_due_time = interval
+            .checked_mul(payload.occurrences as u64 - 1)
+            .and_then(|offset| offset.checked_add(payload.start_time.into()))
+            .expect("Due time of the last occurrence overflows");
+        let mut last_occurrence = payload.template.clone();
+        last_occurrence.settlement_time = Some(U64(last_due_time));
+        self.dr_validate(&last_occurrence, &requestor);
+
+        let fee_per_occurrence: u128 = payload.fee_per_occurrence.into();
+        let escrow = (fee_per_occurrence + validity_bond) * payload.occurrences as u128;
+        assert!(
+            amount >= escrow,
+            "Escrow of {} for {} occurrences not reached, received only {}",
+            escrow,
+            payload.occurrences,
+            amount
+        );
+
+        let recurring_request = RecurringRequest {
+            id: self.recurring_requests.len(),
+            requestor,
+            global_config_id: self.configs.len() - 1,
+            template: payload.template,
+            start_time: payload.start_time.into(),
+            interval,
+            occurrences: payload.occurrences,
+            spawned: 0,
+            fee_per_occurrence,
+            validity_bond,
+            escrow,
+            data_request_ids: Vec::new(),
+            cancelled: false,
+        };
+
+        logger::log_recurring_request(&recurring_request);
+        self.recurring_requests.push(&recurring_request);
+
+        PromiseOrValue::Value(U128(amount - escrow))
+    }
+
+    /**
+     * @notice spawns the `DataRequest`s of a recurring request that are due, can be called by anyone. The storage of the
+     * spawned requests is paid from the requestor's storage balance, like the requests it creates through `ft_transfer_call`
+     * @returns ids of the spawned `DataRequest`s
+     */
+    pub fn dr_spawn_due(&mut self, recurring_request_id: U64) -> Vec<U64> {
+        self.assert_not_paused(pause::Operation::NewRequest);
+
+        let mut recurring_request = self.recurring_request_get_expect(recurring_request_id);
+        let initial_storage = env::storage_usage();
+        let storage_account = self.get_storage_account(&recurring_request.requestor.account_id);
+        assert!(recurring_request.is_due(), "Recurring request with id: {} has no due occurrences", recurring_request.id);
+        // the escrowed validity bond was computed with the requestor's overrides of this config
+        let config = self.configs.get(recurring_request.global_config_id).unwrap().for_requestor(&recurring_request.requestor);
+
+        let mut spawned = Vec::new();
+        while recurring_request.is_due() && spawned.len() < MAX_SPAWNS_PER_CALL as usize {
+            let args = recurring_request.next_occurrence();
+            let dr_id = self.dr_create(
+                recurring_request.requestor.clone(),
+                recurring_request.global_config_id,
+                &config,
+                recurring_request.fee_per_occurrence,
+                args
+            );
+            recurring_request.data_request_ids.push(dr_id);
+            spawned.push(U64(dr_id));
+        }
+
+        self.recurring_requests.replace(recurring_request_id.into(), &recurring_request);
+        logger::log_recurring_request(&recurring_request);
+        self.use_storage(&recurring_request.requestor.account_id, initial_storage, storage_account.available);
+
+        spawned
+    }
+
+    /**
+     * @notice Stops a recurring request and refunds the escrow of the occurrences that weren't spawned yet, only callable
+     * by the creator of the template. Spawned `DataRequest`s aren't affected
+     */
+    pub fn dr_cancel_recurring(&mut self, recurring_request_id: U64) -> Promise {
+        let mut recurring_request = self.recurring_request_get_expect(recurring_request_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            recurring_request.template.creator,
+            "Only the creator can cancel recurring request with id: {}",
+            recurring_request.id
+        );
+        assert!(!recurring_request.cancelled, "Recurring request with id: {} is cancelled", recurring_request.id);
+        assert!(recurring_request.escrow > 0, "Recurring request with id: {} has no escrow left to refund", recurring_request.id);
+
+        let refund = recurring_request.escrow;
+        recurring_request.escrow = 0;
+        recurring_request.cancelled = true;
+        self.recurring_requests.replace(recurring_request_id.into(), &recurring_request);
+        logger::log_recurring_request(&recurring_request);
+
+        let config = self.configs.get(recurring_request.global_config_id).unwrap();
+        fungible_token_transfer(config.payment_token, recurring_request.template.creator, refund)
+    }
+
+    fn recurring_request_get_expect(&self, id: U64) -> RecurringRequest {
+        self.recurring_requests.get(id.into()).expect("ERR_RECURRING_REQUEST_NOT_FOUND")
+    }
+
+    pub fn get_recurring_request(&self, id: U64) -> Option<RecurringRequestSummary> {
+        self.recurring_requests.get(id.into()).map(|recurring_request| recurring_request.summarize())
+    }
+
+    pub fn get_recurring_requests(&self, from_index: U64, limit: U64) -> Vec<RecurringRequestSummary> {
+        let i: u64 = from_index.into();
+        (i..std::cmp::min(i.saturating_add(limit.into()), self.recurring_requests.len()))
+            .map(|index| self.recurring_requests.get(index).unwrap().summarize())
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use near_sdk::{ MockedBlockchain };
+    use near_sdk::{ testing_env, VMContext };
+    use near_sdk::json_types::ValidAccountId;
+    use std::convert::TryInto;
+    use super::*;
+    use fee_config::FeeConfig;
+    use crate::storage_manager::StorageManager;
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn bob() -> AccountId {
+        "bob.near".to_string()
+    }
+
+    fn carol() -> AccountId {
+        "carol.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn gov() -> AccountId {
+        "gov.near".to_string()
+    }
+
+    fn registry_entry(account: AccountId) -> Requestor {
+        Requestor {
+            interface_name: account.clone(),
+            account_id: account.clone(),
+            stake_multiplier: None,
+            code_base_url: None,
+            callback: None,
+            overrides: None
+        }
+    }
+
+    fn config() -> oracle_config::OracleConfig {
+        oracle_config::OracleConfig {
+            gov: gov(),
+            final_arbitrator: alice(),
+            payment_token: token(),
+            stake_token: token(),
+            validity_bond: U128(100),
+            max_outcomes: 8,
+            default_challenge_window_duration: U64(1000),
+            min_initial_challenge_window_duration: U64(1000),
+            final_arbitrator_invoke_amount: U128(250),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
+            fee: FeeConfig {
+                flux_market_cap: U128(50000),
+                total_value_staked: U128(10000),
+                resolution_fee_percentage: 5000, // 5%
+            }
+        }
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: token(),
+            signer_account_id: bob(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 10000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 1000 * 10u128.pow(24),
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    fn recurring_args(occurrences: u16) -> NewRecurringRequestArgs {
+        NewRecurringRequestArgs {
+            template: NewDataRequestArgs {
+                sources: Vec::new(),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: None,
+                data_type: data_request::DataRequestDataType::String,
+                creator: bob(),
+                reveal_period: None,
+                tolerance: None,
+                settlement_time: None,
+                expiry_fallback: None,
+                target_contract: None,
+            },
+            start_time: U64(1000),
+            interval: U64(500),
+            occurrences,
+            fee_per_occurrence: U128(10),
+        }
+    }
+
+    fn unspent(res: PromiseOrValue<WrappedBalance>) -> u128 {
+        match res {
+            PromiseOrValue::Value(value) => value.into(),
+            PromiseOrValue::Promise(_) => panic!("expected value")
+        }
+    }
+
+    // deposits storage for the requestor, spawned requests are paid from it
+    fn recurring_request_with_storage(contract: &mut Contract, occurrences: u16) {
+        let requestor: ValidAccountId = bob().try_into().unwrap();
+        contract.storage_deposit(Some(requestor));
+
+        testing_env!(get_context(token()));
+        contract.dr_new_recurring(bob(), 330, recurring_args(occurrences));
+    }
+
+    fn spawn_at(contract: &mut Contract, timestamp: u64) -> Vec<U64> {
+        let mut ct : VMContext = get_context(alice());
+        ct.block_timestamp = timestamp;
+        testing_env!(ct);
+        contract.dr_spawn_due(U64(0))
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow of 330 for 3 occurrences not reached, received only 329")]
+    fn recurring_escrow_not_reached() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_new_recurring(bob(), 329, recurring_args(3));
+    }
+
+    #[test]
+    fn recurring_refunds_excess() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        assert_eq!(unspent(contract.dr_new_recurring(bob(), 400, recurring_args(3))), 70);
+
+        let recurring_request = contract.get_recurring_request(U64(0)).unwrap();
+        assert_eq!(recurring_request.escrow, U128(330));
+        assert_eq!(recurring_request.next_due_time, Some(U64(1000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement time exceeds maximum settlement time offset of 10000")]
+    fn recurring_last_occurrence_exceeds_settlement_offset() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        // the first occurrence is due at 1000, the 20th at 10500
+        contract.dr_new_recurring(bob(), 2200, recurring_args(20));
+    }
+
+    #[test]
+    #[should_panic(expected = "Recurring request with id: 0 has no due occurrences")]
+    fn recurring_spawn_not_due() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_new_recurring(bob(), 330, recurring_args(3));
+
+        spawn_at(&mut contract, 999);
+    }
+
+    #[test]
+    fn recurring_spawn_due() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        recurring_request_with_storage(&mut contract, 3);
+
+        assert_eq!(spawn_at(&mut contract, 1500), vec![U64(0), U64(1)]);
+        assert!(contract.get_storage_account(&bob()).available < 1000 * 10u128.pow(24));
+        assert_eq!(contract.get_storage_account(&alice()).available, 0);
+
+        let request = contract.get_request_by_id(U64(1)).unwrap();
+        assert_eq!(request.settlement_time, Some(U64(1500)));
+        assert_eq!(request.request_config.paid_fee, U128(10));
+        assert_eq!(request.requestor.account_id, bob());
+
+        let recurring_request = contract.get_recurring_request(U64(0)).unwrap();
+        assert_eq!(recurring_request.spawned, 2);
+        assert_eq!(recurring_request.escrow, U128(110));
+        assert_eq!(recurring_request.next_due_time, Some(U64(2000)));
+
+        assert_eq!(spawn_at(&mut contract, 5000), vec![U64(2)]);
+        let recurring_request = contract.get_recurring_request(U64(0)).unwrap();
+        assert_eq!(recurring_request.escrow, U128(0));
+        assert_eq!(recurring_request.next_due_time, None);
+
+        // Occurrences can be staked on like any other request
+        let mut ct : VMContext = get_context(token());
+        ct.block_timestamp = 5000;
+        testing_env!(ct);
+        contract.dr_stake(alice(), 10, StakeDataRequestArgs{
+            id: U64(2),
+            outcome: Outcome::Answer(AnswerType::String("a".to_string()))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near has 0 deposited")]
+    fn recurring_spawn_without_requestor_storage() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_new_recurring(bob(), 330, recurring_args(3));
+
+        spawn_at(&mut contract, 1000);
+    }
+
+    #[test]
+    fn recurring_cancel_refunds_unspawned() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        recurring_request_with_storage(&mut contract, 3);
+        spawn_at(&mut contract, 1000);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel_recurring(U64(0));
+
+        let recurring_request = contract.get_recurring_request(U64(0)).unwrap();
+        assert!(recurring_request.cancelled);
+        assert_eq!(recurring_request.escrow, U128(0));
+        assert_eq!(recurring_request.next_due_time, None);
+        assert_eq!(recurring_request.data_request_ids, vec![U64(0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Recurring request with id: 0 has no due occurrences")]
+    fn recurring_spawn_after_cancel() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        recurring_request_with_storage(&mut contract, 3);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel_recurring(U64(0));
+
+        spawn_at(&mut contract, 5000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator can cancel recurring request with id: 0")]
+    fn recurring_cancel_from_user() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_new_recurring(bob(), 330, recurring_args(3));
+
+        testing_env!(get_context(alice()));
+        contract.dr_cancel_recurring(U64(0));
+    }
+
+    #[test]
+    fn recurring_requests_max_limit() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.dr_new_recurring(bob(), 330, recurring_args(3));
+
+        assert_eq!(contract.get_recurring_requests(U64(0), U64(u64::MAX)).len(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::*;
+
+use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
+use near_sdk::serde::{ Serialize, Deserialize };
+use near_sdk::AccountId;
+use near_sdk::collections::Vector;
+
+use crate::data_request::DataRequestSummary;
+
+const MAX_QUERY_LIMIT: u64 = 100;
+
+/**
+ * @notice Lifecycle states of a `DataRequest`. `Finalizable` depends on the block time so it's never stored in the
+ * state index, those requests are stored as `InDispute`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum DataRequestState {
+    AwaitingFirstStake,
+    InDispute,
+    ArbitratorTriggered,
+    Finalizable,
+    Finalized,
+    Cancelled,
+}
+
+impl DataRequestState {
+    fn index_key(&self) -> u8 {
+        match self {
+            DataRequestState::AwaitingFirstStake => 0,
+            DataRequestState::InDispute | DataRequestState::Finalizable => 1,
+            DataRequestState::ArbitratorTriggered => 2,
+            DataRequestState::Finalized => 3,
+            DataRequestState::Cancelled => 4,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum RequestFilter {
+    All,
+    Requestor(AccountId),
+    Creator(AccountId),
+    Tag(String),
+    State(DataRequestState),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestPage {
+    pub requests: Vec<DataRequestSummary>,
+    pub next_cursor: Option<U64>, // `None` when there are no more results
+}
+
+// @returns the amount of ids in the sorted `ids` that are lower than `id`
+fn lower_bound(ids: &Vector<u64>, id: u64) -> u64 {
+    let mut low = 0;
+    let mut high = ids.len();
+    while low < high {
+        let mid = (low + high) / 2;
+        if ids.get(mid).unwrap() < id {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+// @returns the ids in the sorted `ids` that come after `cursor` in the given direction
+fn ids_after_cursor<'a>(ids: &'a Vector<u64>, cursor: Option<u64>, reverse: bool) -> Box<dyn Iterator<Item = u64> + 'a> {
+    if reverse {
+        let end = cursor.map(|cursor| lower_bound(ids, cursor)).unwrap_or_else(|| ids.len());
+        Box::new((0..end).rev().map(move |i| ids.get(i).unwrap()))
+    } else {
+        let start = cursor.map(|cursor| lower_bound(ids, cursor.saturating_add(1))).unwrap_or(0);
+        Box::new((start..ids.len()).map(move |i| ids.get(i).unwrap()))
+    }
+}
+
+fn push_id(index: &mut LookupMap<String, Vector<u64>>, prefix: &str, key: &str, id: u64) {
+    let mut ids = index
+        .get(&key.to_string())
+        .unwrap_or_else(|| Vector::new(format!("{}:{}", prefix, key).as_bytes().to_vec()));
+    // Ids are pushed in increasing order, this only skips duplicate tags
+    if ids.len() == 0 || ids.get(ids.len() - 1).unwrap() != id {
+        ids.push(&id);
+        index.insert(&key.to_string(), &ids);
+    }
+}
+
+impl Contract {
+    // @notice Adds a new `DataRequest` to all secondary indexes
+    pub fn index_new_request(&mut self, dr: &DataRequest) {
+        push_id(&mut self.requests_by_requestor, "rbr", &dr.requestor.account_id, dr.id);
+        push_id(&mut self.requests_by_creator, "rbc", &dr.creator, dr.id);
+        if let Some(tags) = &dr.tags {
+            for tag in tags {
+                push_id(&mut self.requests_by_tag, "rbt", tag, dr.id);
+            }
+        }
+        self.request_states.insert(&(dr.stored_state().index_key(), dr.id), &());
+    }
+
+    // @notice Moves `dr` in the state index, should be called after every state transition
+    pub fn index_request_state(&mut self, prev_state: DataRequestState, dr: &DataRequest) {
+        let new_state = dr.stored_state();
+        if prev_state.index_key() != new_state.index_key() {
+            self.request_states.remove(&(prev_state.index_key(), dr.id));
+            self.request_states.insert(&(new_state.index_key(), dr.id), &());
+        }
+    }
+
+    fn request_ids_in_state<'a>(&'a self, state: DataRequestState, cursor: Option<u64>, reverse: bool) -> Box<dyn Iterator<Item = u64> + 'a> {
+        let key = state.index_key();
+        let ids: Box<dyn Iterator<Item = ((u8, u64), ())> + 'a> = if reverse {
+            Box::new(self.request_states.iter_rev_from((key, cursor.unwrap_or(u64::MAX))))
+        } else {
+            match cursor {
+                Some(cursor) => Box::new(self.request_states.iter_from((key, cursor))),
+                None => Box::new(self.request_states.range((std::ops::Bound::Included((key, 0)), std::ops::Bound::Included((key, u64::MAX)))))
+            }
+        };
+
+        Box::new(ids.take_while(move |((state_key, _), _)| *state_key == key).map(|((_, id), _)| id))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Pages through `DataRequest`s matching `filter`, ordered by id
+     * @param cursor id of the last request of the previous page, `None` for the first page
+     */
+    pub fn get_requests_filtered(&self, filter: RequestFilter, cursor: Option<U64>, limit: U64, reverse: Option<bool>) -> RequestPage {
+        let cursor: Option<u64> = cursor.map(u64::from);
+        let reverse = reverse.unwrap_or(false);
+        let limit = std::cmp::min(u64::from(limit), MAX_QUERY_LIMIT) as usize;
+
+        let empty_ids: Vector<u64> = Vector::new(b"rq_empty".to_vec());
+        let index_ids;
+        let ids: Box<dyn Iterator<Item = u64> + '_> = match &filter {
+            RequestFilter::All => {
+                let total = self.data_requests.len();
+                if reverse {
+                    Box::new((0..cursor.unwrap_or(total)).rev())
+                } else {
+                    Box::new(cursor.map(|cursor| cursor.saturating_add(1)).unwrap_or(0)..total)
+                }
+            },
+            RequestFilter::Requestor(key) | RequestFilter::Creator(key) | RequestFilter::Tag(key) => {
+                let index = match &filter {
+                    RequestFilter::Requestor(_) => &self.requests_by_requestor,
+                    RequestFilter::Creator(_) => &self.requests_by_creator,
+                    _ => &self.requests_by_tag
+                };
+                index_ids = index.get(key);
+                ids_after_cursor(index_ids.as_ref().unwrap_or(&empty_ids), cursor, reverse)
+            },
+            RequestFilter::State(DataRequestState::Finalizable) => Box::new(
+                self.request_ids_in_state(DataRequestState::InDispute, cursor, reverse)
+                    .filter(move |id| self.data_requests.get(*id).unwrap().state() == DataRequestState::Finalizable)
+            ),
+            RequestFilter::State(DataRequestState::InDispute) => Box::new(
+                self.request_ids_in_state(DataRequestState::InDispute, cursor, reverse)
+                    .filter(move |id| self.data_requests.get(*id).unwrap().state() == DataRequestState::InDispute)
+            ),
+            RequestFilter::State(state) => self.request_ids_in_state(*state, cursor, reverse)
+        };
+
+        let requests: Vec<DataRequestSummary> = ids
+            .take(limit)
+            .map(|id| self.get_request_by_id(U64(id)).unwrap())
+            .collect();
+
+        let next_cursor = if requests.len() == limit {
+            requests.last().map(|request| U64(request.id))
+        } else {
+            None
+        };
+
+        RequestPage {
+            requests,
+            next_cursor,
+        }
+    }
+}
@@ -1,12 +1,53 @@
 use crate::*;
-use near_sdk::{PromiseOrValue, ext_contract, Gas, Promise};
+use near_sdk::{PromiseOrValue, PromiseResult, ext_contract, Gas, Promise};
 use near_sdk::serde::{Serialize, Deserialize};
+use near_sdk::serde_json::json;
 
 const GAS_BASE_SET_OUTCOME: Gas = 250_000_000_000_000;
+const GAS_SET_OUTCOME: Gas = GAS_BASE_SET_OUTCOME / 10;
+const GAS_ON_OUTCOME_DELIVERED: Gas = 5_000_000_000_000;
+const MAX_CALLBACK_GAS: Gas = 100_000_000_000_000;
+const MAX_CALLBACK_DEPOSIT: Balance = 10_000_000_000_000_000_000_000; // 0.01 NEAR, only attached on retries which are paid by the caller
 
 #[ext_contract]
 pub trait RequestorContractExtern {
-    fn set_outcome(request_id: U64, requestor: AccountId, outcome: Outcome, tags: Option<Vec<String>>, final_arbitrator_triggered: bool);
+    fn set_outcome(request_id: U64, requestor: AccountId, outcome: Outcome, tags: Option<Vec<String>>, final_arbitrator_triggered: bool, arbitration_fallback_used: bool);
+}
+
+/**
+ * @notice Overrides how the outcome is delivered to a requestor, by default `set_outcome` is called with 1 yocto and a tenth of 250 TGas.
+ * The first delivery attaches no deposit since nothing was escrowed for it, the `deposit` is attached when the delivery is retried
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RequestorCallback {
+    pub method_name: String,
+    pub gas: U64,
+    pub deposit: U128,
+    pub include_tags: bool, // when `false` the `tags` argument is always `null`
+}
+
+impl RequestorCallback {
+    pub fn assert_valid(&self) {
+        assert!(!self.method_name.is_empty(), "Callback method name can't be empty");
+        assert!(
+            u64::from(self.gas) > 0 && u64::from(self.gas) <= MAX_CALLBACK_GAS,
+            "Callback gas should be between 1 and {}",
+            MAX_CALLBACK_GAS
+        );
+        assert!(u128::from(self.deposit) <= MAX_CALLBACK_DEPOSIT, "Callback deposit can't exceed {}", MAX_CALLBACK_DEPOSIT);
+    }
+}
+
+/**
+ * @notice Replaces `OracleConfig` parameters for the `DataRequest`s of a single requestor, applied within the config's `requestor_bounds`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RequestorOverrides {
+    pub validity_bond: Option<U128>,
+    pub min_initial_challenge_window_duration: Option<WrappedTimestamp>,
+    pub default_challenge_window_duration: Option<WrappedTimestamp>, // also caps the challenge period at 3 times this duration
+    pub max_outcomes: Option<u8>,
+    pub final_arbitrator_invoke_amount: Option<U128>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
@@ -14,12 +55,28 @@ pub struct Requestor {
     pub interface_name: String,
     pub account_id: AccountId, // Change to account_id
     pub stake_multiplier: Option<u16>, 
-    pub code_base_url: Option<String>
+    pub code_base_url: Option<String>,
+    pub callback: Option<RequestorCallback>,
+    pub overrides: Option<RequestorOverrides>,
 }
 
 #[ext_contract(ext_self)]
 trait SelfExt {
     fn proceed_dr_new(&mut self, sender: AccountId, amount: Balance, payload: NewDataRequestArgs);
+    fn on_outcome_delivered(&mut self, request_id: U64, recipients: Vec<OutcomeRecipient>);
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum OutcomeDelivery {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum OutcomeRecipient {
+    Requestor,
+    TargetContract,
 }
 
 impl Requestor {
@@ -28,28 +85,138 @@ impl Requestor {
             interface_name: "".to_string(),
             account_id: account_id.to_string(),
             stake_multiplier: None,
-            code_base_url: None
+            code_base_url: None,
+            callback: None,
+            overrides: None,
         }
     }
+
+    // @param gas overrides the gas of the callback, used when retrying a failed delivery which also attaches the callback's deposit
     pub fn set_outcome(
         &self,
         request_id: U64,
         outcome: Outcome,
         tags: Option<Vec<String>>,
-        final_arbitrator_triggered: bool
+        final_arbitrator_triggered: bool,
+        arbitration_fallback_used: bool, // the final arbitrator timed out and the outcome is the configured fallback
+        gas: Option<Gas>
     ) -> Promise {
-        requestor_contract_extern::set_outcome(
-            request_id,
-            self.account_id.to_string(),
-            outcome,
-            tags,
-            final_arbitrator_triggered,
-
-            // NEAR params
-            &self.account_id,
-            1, 
-            GAS_BASE_SET_OUTCOME / 10,
-        )
+        match &self.callback {
+            Some(callback) => {
+                let args = json!({
+                    "request_id": request_id,
+                    "requestor": self.account_id,
+                    "outcome": outcome,
+                    "tags": if callback.include_tags { tags } else { None },
+                    "final_arbitrator_triggered": final_arbitrator_triggered,
+                    "arbitration_fallback_used": arbitration_fallback_used,
+                });
+
+                // the caller of `dr_retry_outcome_delivery` pays the deposit, the oracle's own balance never does
+                let deposit = match gas {
+                    Some(_) => callback.deposit.into(),
+                    None => 0
+                };
+
+                Promise::new(self.account_id.to_string()).function_call(
+                    callback.method_name.as_bytes().to_vec(),
+                    args.to_string().into_bytes(),
+                    deposit,
+                    gas.unwrap_or_else(|| callback.gas.into())
+                )
+            },
+            None => requestor_contract_extern::set_outcome(
+                request_id,
+                self.account_id.to_string(),
+                outcome,
+                tags,
+                final_arbitrator_triggered,
+                arbitration_fallback_used,
+
+                // NEAR params
+                &self.account_id,
+                1, 
+                gas.unwrap_or(GAS_SET_OUTCOME),
+            )
+        }
+    }
+}
+
+impl DataRequest {
+    /**
+     * @notice Sends the finalized outcome to every recipient that didn't receive it yet, the result is recorded in `on_outcome_delivered`
+     * @param gas overrides the gas of every `set_outcome` call, used when retrying a failed delivery
+     */
+    pub fn deliver_outcome(&mut self, gas: Option<Gas>) -> Promise {
+        if self.outcome_delivery.is_none() {
+            self.undelivered_recipients = self.outcome_recipients();
+        }
+        self.outcome_delivery = Some(OutcomeDelivery::Pending);
+
+        let recipients = self.undelivered_recipients.clone();
+        let mut promise: Option<Promise> = None;
+        for recipient in &recipients {
+            let recipient_promise = self.send_outcome(*recipient, gas);
+            promise = Some(match promise {
+                Some(promise) => promise.and(recipient_promise),
+                None => recipient_promise
+            });
+        }
+
+        promise.expect("No recipients left to deliver the outcome to").then(ext_self::on_outcome_delivered(
+                U64(self.id),
+                recipients,
+                &env::current_account_id(),
+                0,
+                GAS_ON_OUTCOME_DELIVERED
+            ))
+    }
+
+    // @returns the recipients of the outcome, in the order their `set_outcome` calls are joined
+    pub fn outcome_recipients(&self) -> Vec<OutcomeRecipient> {
+        match &self.target_contract {
+            Some(target) if target.also_notify_requestor => vec![OutcomeRecipient::Requestor, OutcomeRecipient::TargetContract],
+            Some(_) => vec![OutcomeRecipient::TargetContract],
+            None => vec![OutcomeRecipient::Requestor]
+        }
+    }
+
+    // @returns the deposit the `set_outcome` calls of a retry attach, covered by the caller of `dr_retry_outcome_delivery`
+    pub fn retry_deposit(&self) -> Balance {
+        let requestor_deposit = match &self.requestor.callback {
+            Some(callback) => callback.deposit.into(),
+            None => 1
+        };
+
+        self.undelivered_recipients.iter().map(|recipient| match recipient {
+            OutcomeRecipient::Requestor => requestor_deposit,
+            OutcomeRecipient::TargetContract => 1
+        }).sum()
+    }
+
+    fn send_outcome(&self, recipient: OutcomeRecipient, gas: Option<Gas>) -> Promise {
+        match recipient {
+            OutcomeRecipient::Requestor => self.requestor.set_outcome(
+                U64(self.id),
+                self.finalized_outcome.clone().unwrap(),
+                self.tags.clone(),
+                self.final_arbitrator_triggered,
+                self.arbitration_fallback_used,
+                gas
+            ),
+            OutcomeRecipient::TargetContract => {
+                let target = self.target_contract.as_ref().unwrap();
+                target.set_outcome(
+                    U64(self.id),
+                    self.requestor.account_id.to_string(),
+                    self.finalized_outcome.clone().unwrap(),
+                    self.tags.clone(),
+                    self.final_arbitrator_triggered,
+                    self.arbitration_fallback_used,
+                    gas.unwrap_or_else(|| target.gas.into())
+                )
+            }
+        }
     }
 }
 
@@ -68,4 +235,85 @@ impl Contract {
     ) -> PromiseOrValue<WrappedBalance> {
         PromiseOrValue::Value(U128(self.dr_new(sender.clone(), amount.into(), payload)))
     }
+
+    /**
+     * @notice called after `set_outcome` to record which of the `recipients` received the outcome
+     * @param recipients the recipients the outcome was sent to, in the order of the promise results
+     */
+    #[private]
+    pub fn on_outcome_delivered(&mut self, request_id: U64, recipients: Vec<OutcomeRecipient>) -> bool {
+        let results = (0..env::promise_results_count()).map(env::promise_result).collect();
+        self.record_outcome_delivery(request_id, recipients, results)
+    }
+
+    /**
+     * @notice Sends the outcome of a finalized `DataRequest` again to the recipients that didn't receive it, callable by anyone
+     * @param gas the gas attached to every `set_outcome` call, the transaction should have enough prepaid gas to cover them
+     * @notice the caller attaches the requestor's callback deposit, if the requestor is retried
+     */
+    #[payable]
+    pub fn dr_retry_outcome_delivery(&mut self, request_id: U64, gas: U64) -> Promise {
+        let gas: Gas = gas.into();
+        let mut dr = self.data_requests.get(request_id.into()).expect("ERR_DATA_REQUEST_NOT_FOUND");
+        assert_eq!(
+            dr.outcome_delivery,
+            Some(OutcomeDelivery::Failed),
+            "Outcome delivery of `DataRequest` with id: {} didn't fail",
+            dr.id
+        );
+        assert!(
+            env::prepaid_gas() - env::used_gas() > gas * dr.undelivered_recipients.len() as u64 + GAS_ON_OUTCOME_DELIVERED,
+            "Not enough gas attached to deliver the outcome with {} gas",
+            gas
+        );
+        let deposit = dr.retry_deposit();
+        assert_eq!(
+            env::attached_deposit(),
+            deposit,
+            "Retrying the outcome delivery of `DataRequest` with id: {} requires an attached deposit of {}",
+            dr.id,
+            deposit
+        );
+
+        let promise = dr.deliver_outcome(Some(gas));
+        self.failed_deliveries.remove(&dr.id);
+        self.data_requests.replace(request_id.into(), &dr);
+
+        logger::log_update_data_request(&dr);
+        promise
+    }
+
+    pub fn get_failed_outcome_deliveries(&self, from_index: U64, limit: U64) -> Vec<U64> {
+        let ids = self.failed_deliveries.as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i.saturating_add(limit.into()), ids.len()))
+            .map(|index| U64(ids.get(index).unwrap()))
+            .collect()
+    }
+}
+
+impl Contract {
+    // @returns whether every recipient has received the outcome
+    pub(crate) fn record_outcome_delivery(&mut self, request_id: U64, recipients: Vec<OutcomeRecipient>, results: Vec<PromiseResult>) -> bool {
+        let mut dr = self.data_requests.get(request_id.into()).expect("ERR_DATA_REQUEST_NOT_FOUND");
+        for (recipient, result) in recipients.iter().zip(results.iter()) {
+            if let PromiseResult::Successful(_) = result {
+                dr.undelivered_recipients.retain(|undelivered| undelivered != recipient);
+            }
+        }
+
+        let delivered = dr.undelivered_recipients.is_empty();
+        if delivered {
+            dr.outcome_delivery = Some(OutcomeDelivery::Delivered);
+            self.failed_deliveries.remove(&dr.id);
+        } else {
+            dr.outcome_delivery = Some(OutcomeDelivery::Failed);
+            self.failed_deliveries.insert(&dr.id);
+        }
+        self.data_requests.replace(request_id.into(), &dr);
+
+        logger::log_update_data_request(&dr);
+        delivered
+    }
 }
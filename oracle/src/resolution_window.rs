@@ -1,10 +1,12 @@
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::serde::{ Deserialize, Serialize };
-use near_sdk::{ Balance, AccountId };
-use near_sdk::collections::{ LookupMap };
+use near_sdk::{ env, Balance, AccountId };
+use near_sdk::collections::{ LookupMap, TreeMap, UnorderedSet };
 
 use crate::types::*;
+use crate::helpers;
 use crate::logger;
+use crate::data_request::NumberTolerance;
 
 pub enum WindowStakeResult {
     Incorrect(Balance), // Round bonded outcome was correct
@@ -26,7 +28,25 @@ pub struct ResolutionWindow {
     pub bond_size: Balance,
     pub outcome_to_stake: LookupMap<Outcome, Balance>,
     pub user_to_outcome_to_stake: LookupMap<AccountId, LookupMap<Outcome, Balance>>,
+    pub user_to_outcomes: LookupMap<AccountId, Vec<Outcome>>, // outcomes each user staked on, used to refund non-bonded stake on claim
     pub bonded_outcome: Option<Outcome>,
+    pub commit_end_time: Option<Timestamp>, // Only set for commit-reveal windows, reveal phase runs from here until `end_time`
+    pub user_to_commitment: LookupMap<AccountId, Commitment>,
+    pub total_committed: Balance,
+    pub total_revealed: Balance,
+    // Only used for requests with a `NumberTolerance`, stakes on outcomes inside the same band are pooled into a bucket
+    pub outcome_to_bucket: LookupMap<Outcome, Outcome>,
+    pub anchors: TreeMap<i128, Outcome>, // value of every bucket's anchor, ordered so the nearest anchors of an outcome can be found
+    pub bucket_to_members: LookupMap<Outcome, Vec<Outcome>>,
+    pub member_to_stake: LookupMap<Outcome, Balance>,
+    pub bonded_bucket: Option<Outcome>,
+    pub staked_outcomes: UnorderedSet<Outcome>, // every outcome staked on in this window, used to prune the maps above
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Commitment {
+    pub hash: String, // hex encoded sha256, see `helpers::calc_commitment`
+    pub amount: Balance,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -35,29 +55,118 @@ pub struct ResolutionWindowSummary {
     pub start_time: WrappedTimestamp, 
     pub end_time: WrappedTimestamp,
     pub bond_size: WrappedBalance,
-    pub bonded_outcome: Option<Outcome>
+    pub bonded_outcome: Option<Outcome>,
+    pub commit_end_time: Option<WrappedTimestamp>,
+    pub total_committed: WrappedBalance,
+    pub total_revealed: WrappedBalance,
 }
 
 impl ResolutionWindow {
-    pub fn new(dr_id: u64, round: u16, prev_bond: Balance, challenge_period: u64, start_time: u64) -> Self {
-        let new_resolution_window = Self {
+    pub fn new(dr_id: u64, round: u16, bond_size: Balance, challenge_period: u64, reveal_period: Option<u64>, start_time: u64) -> Self {
+        let new_resolution_window = Self::create(dr_id, round, bond_size, challenge_period, reveal_period, start_time);
+
+        logger::log_resolution_window(&new_resolution_window);
+        return new_resolution_window;
+    }
+
+    // @notice Same as `new` without logging the window, used to convert the windows of baseline `DataRequest`s
+    pub(crate) fn create(dr_id: u64, round: u16, bond_size: Balance, challenge_period: u64, reveal_period: Option<u64>, start_time: u64) -> Self {
+        // Commit-reveal windows use the challenge period for commits and append the reveal period after it
+        let (commit_end_time, end_time) = match reveal_period {
+            Some(reveal_period) => (Some(start_time + challenge_period), start_time + challenge_period + reveal_period),
+            None => (None, start_time + challenge_period)
+        };
+
+        Self {
             dr_id,
             round,
             start_time,
-            end_time: start_time + challenge_period,
-            bond_size: prev_bond * 2,
+            end_time,
+            bond_size,
             outcome_to_stake: LookupMap::new(format!("ots{}:{}", dr_id, round).as_bytes().to_vec()),
             user_to_outcome_to_stake: LookupMap::new(format!("utots{}:{}", dr_id, round).as_bytes().to_vec()),
-            bonded_outcome: None
+            user_to_outcomes: LookupMap::new(format!("uto{}:{}", dr_id, round).as_bytes().to_vec()),
+            bonded_outcome: None,
+            commit_end_time,
+            user_to_commitment: LookupMap::new(format!("utc{}:{}", dr_id, round).as_bytes().to_vec()),
+            total_committed: 0,
+            total_revealed: 0,
+            outcome_to_bucket: LookupMap::new(format!("otb{}:{}", dr_id, round).as_bytes().to_vec()),
+            anchors: TreeMap::new(format!("anc{}:{}", dr_id, round).as_bytes().to_vec()),
+            bucket_to_members: LookupMap::new(format!("btm{}:{}", dr_id, round).as_bytes().to_vec()),
+            member_to_stake: LookupMap::new(format!("mts{}:{}", dr_id, round).as_bytes().to_vec()),
+            bonded_bucket: None,
+            staked_outcomes: UnorderedSet::new(format!("sto{}:{}", dr_id, round).as_bytes().to_vec()),
+        }
+    }
+
+    /**
+     * @notice Finds the bucket `outcome` is pooled into, the anchors whose band contains `outcome` form a contiguous
+     *  range of values around it so only the nearest anchor below and above have to be checked
+     * @returns the outcome that anchors the bucket, which is `outcome` itself if there's no tolerance
+     */
+    fn resolve_bucket(&mut self, outcome: &Outcome, tolerance: Option<&NumberTolerance>) -> Outcome {
+        let (tolerance, value) = match (tolerance, outcome.number_value()) {
+            (Some(tolerance), Some(value)) => (tolerance, value),
+            _ => return outcome.clone()
         };
 
-        logger::log_resolution_window(&new_resolution_window);
-        return new_resolution_window;
+        if let Some(bucket) = self.outcome_to_bucket.get(outcome) {
+            return bucket;
+        }
+
+        let anchor = [self.anchors.floor_key(&value), self.anchors.ceil_key(&value)]
+            .iter()
+            .flatten()
+            .filter_map(|anchor_value| self.anchors.get(anchor_value))
+            .find(|anchor| tolerance.contains(anchor, outcome));
+
+        let bucket = match anchor {
+            Some(anchor) => anchor,
+            None => {
+                self.anchors.insert(&value, outcome);
+                outcome.clone()
+            }
+        };
+
+        let mut members = self.bucket_to_members.get(&bucket).unwrap_or_default();
+        members.push(outcome.clone());
+        self.bucket_to_members.insert(&bucket, &members);
+        self.outcome_to_bucket.insert(outcome, &bucket);
+
+        bucket
+    }
+
+    // @returns the stake weighted median of the outcomes in `bucket`
+    fn bucket_median(&self, bucket: &Outcome) -> Outcome {
+        let mut members: Vec<(i128, Outcome, Balance)> = self.bucket_to_members
+            .get(bucket)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|member| {
+                let stake = self.member_to_stake.get(&member).unwrap_or(0);
+                member.number_value().map(|value| (value, member, stake))
+            })
+            .collect();
+        members.sort_by_key(|(value, _, _)| *value);
+
+        let total_stake: Balance = members.iter().map(|(_, _, stake)| stake).sum();
+        let mut cumulative_stake = 0;
+        for (_, member, stake) in members {
+            cumulative_stake += stake;
+            if cumulative_stake * 2 >= total_stake {
+                return member;
+            }
+        }
+
+        bucket.clone()
     }
 
     // @returns amount to refund users because it was not staked
-    pub fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance {
-        let stake_on_outcome = self.outcome_to_stake.get(&outcome).unwrap_or(0);
+    pub fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance, tolerance: Option<&NumberTolerance>) -> Balance {
+        let bucket = self.resolve_bucket(&outcome, tolerance);
+        self.staked_outcomes.insert(&outcome);
+        let stake_on_outcome = self.outcome_to_stake.get(&bucket).unwrap_or(0);
         let mut user_to_outcomes = self.user_to_outcome_to_stake
             .get(&sender)
             .unwrap_or(LookupMap::new(format!("utots:{}:{}:{}", self.dr_id, self.round, sender).as_bytes().to_vec()));
@@ -73,19 +182,34 @@ impl ResolutionWindow {
         let staked = amount - unspent;
 
         let new_stake_on_outcome = stake_on_outcome + staked;
-        self.outcome_to_stake.insert(&outcome, &new_stake_on_outcome);
-        logger::log_outcome_to_stake(self.dr_id, self.round, &outcome, new_stake_on_outcome);
+        self.outcome_to_stake.insert(&bucket, &new_stake_on_outcome);
+        logger::log_outcome_to_stake(self.dr_id, self.round, &bucket, new_stake_on_outcome);
+
+        if tolerance.is_some() {
+            let member_stake = self.member_to_stake.get(&outcome).unwrap_or(0);
+            self.member_to_stake.insert(&outcome, &(member_stake + staked));
+        }
 
         let new_user_stake_on_outcome = user_stake_on_outcome + staked;
-        user_to_outcomes.insert(&outcome, &new_user_stake_on_outcome);
-        self.user_to_outcome_to_stake.insert(&sender, &user_to_outcomes);
+        if staked > 0 {
+            user_to_outcomes.insert(&outcome, &new_user_stake_on_outcome);
+            self.user_to_outcome_to_stake.insert(&sender, &user_to_outcomes);
+
+            let mut staked_outcomes = self.user_to_outcomes.get(&sender).unwrap_or_default();
+            if !staked_outcomes.contains(&outcome) {
+                staked_outcomes.push(outcome.clone());
+                self.user_to_outcomes.insert(&sender, &staked_outcomes);
+            }
+        }
 
         logger::log_user_stake(self.dr_id, self.round, &sender, &outcome, new_user_stake_on_outcome);
         logger::log_stake_transaction(&sender, &self, amount, unspent, &outcome);
 
         // If this stake fills the bond set final outcome which will trigger a new resolution_window to be created
+        // Buckets bond on the stake weighted median of the answers inside of them
         if new_stake_on_outcome == self.bond_size {
-            self.bonded_outcome = Some(outcome);
+            self.bonded_outcome = Some(if tolerance.is_some() { self.bucket_median(&bucket) } else { outcome });
+            self.bonded_bucket = Some(bucket);
             logger::log_resolution_window(&self);
         }
 
@@ -94,18 +218,23 @@ impl ResolutionWindow {
 
     // @returns amount to refund users because it was not staked
     pub fn unstake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance {
-        assert!(self.bonded_outcome.is_none() || self.bonded_outcome.as_ref().unwrap() != &outcome, "Cannot withdraw from bonded outcome");
+        let bucket = self.outcome_to_bucket.get(&outcome).unwrap_or_else(|| outcome.clone());
+        assert!(self.bonded_bucket.is_none() || self.bonded_bucket.as_ref().unwrap() != &bucket, "Cannot withdraw from bonded outcome");
         let mut user_to_outcomes = self.user_to_outcome_to_stake
             .get(&sender)
             .unwrap_or(LookupMap::new(format!("utots:{}:{}:{}", self.dr_id, self.round, sender).as_bytes().to_vec()));
         let user_stake_on_outcome = user_to_outcomes.get(&outcome).unwrap_or(0);
         assert!(user_stake_on_outcome >= amount, "{} has less staked on this outcome ({}) than unstake amount", sender, user_stake_on_outcome);
 
-        let stake_on_outcome = self.outcome_to_stake.get(&outcome).unwrap_or(0);
+        let stake_on_outcome = self.outcome_to_stake.get(&bucket).unwrap_or(0);
 
         let new_stake_on_outcome = stake_on_outcome - amount;
-        self.outcome_to_stake.insert(&outcome, &new_stake_on_outcome);
-        logger::log_outcome_to_stake(self.dr_id, self.round, &outcome, new_stake_on_outcome);
+        self.outcome_to_stake.insert(&bucket, &new_stake_on_outcome);
+        logger::log_outcome_to_stake(self.dr_id, self.round, &bucket, new_stake_on_outcome);
+
+        if let Some(member_stake) = self.member_to_stake.get(&outcome) {
+            self.member_to_stake.insert(&outcome, &(member_stake - amount));
+        }
 
         let new_user_stake_on_outcome = user_stake_on_outcome - amount;
         user_to_outcomes.insert(&outcome, &new_user_stake_on_outcome);
@@ -116,18 +245,112 @@ impl ResolutionWindow {
         amount
     }
 
-    pub fn claim_for(&mut self, account_id: AccountId, final_outcome: &Outcome) -> WindowStakeResult {
+    pub fn commit(&mut self, sender: AccountId, hash: String, amount: Balance) {
+        let commit_end_time = self.commit_end_time.expect("ERR_NOT_COMMIT_REVEAL_WINDOW");
+        assert!(env::block_timestamp() < commit_end_time, "Commit phase of round {} has ended", self.round);
+        assert!(self.user_to_commitment.get(&sender).is_none(), "{} already committed in round {}", sender, self.round);
+
+        self.user_to_commitment.insert(&sender, &Commitment { hash, amount });
+        self.total_committed += amount;
+
+        logger::log_commit_transaction(&sender, &self, amount);
+        logger::log_resolution_window(&self);
+    }
+
+    // @returns amount to refund users because it was not staked
+    pub fn reveal(&mut self, sender: AccountId, outcome: Outcome, salt: String, tolerance: Option<&NumberTolerance>) -> Balance {
+        let commit_end_time = self.commit_end_time.expect("ERR_NOT_COMMIT_REVEAL_WINDOW");
+        let now = env::block_timestamp();
+        assert!(now >= commit_end_time && now < self.end_time, "Can only reveal during the reveal phase of round {}", self.round);
+
+        let commitment = self.user_to_commitment.remove(&sender).expect("ERR_NO_COMMITMENT");
+        assert_eq!(
+            helpers::calc_commitment(&sender, &outcome, &salt),
+            commitment.hash,
+            "Revealed outcome and salt do not match the commitment"
+        );
+        self.total_revealed += commitment.amount;
+        logger::log_reveal_transaction(&sender, &self, commitment.amount, &outcome);
+        logger::log_resolution_window(&self);
+
+        // If another reveal already filled the bond the revealed stake is returned
+        if self.bonded_outcome.is_some() {
+            return commitment.amount;
+        }
+
+        self.stake(sender, outcome, commitment.amount, tolerance)
+    }
+
+    // @returns the stake `account_id` committed but never revealed in this window
+    pub fn claim_unrevealed_for(&mut self, account_id: &AccountId) -> Balance {
+        match self.user_to_commitment.remove(account_id) {
+            Some(commitment) => commitment.amount,
+            None => 0
+        }
+    }
+
+    fn is_in_bonded_bucket(&self, outcome: &Outcome) -> bool {
+        match &self.bonded_bucket {
+            Some(bonded_bucket) => &self.outcome_to_bucket.get(outcome).unwrap_or_else(|| outcome.clone()) == bonded_bucket,
+            None => false
+        }
+    }
+
+    /**
+     * @notice Removes the stake `account_id` has on outcomes that didn't get bonded in this window, stake on the bonded
+     * outcome is handled by `claim_for`
+     * @returns the amount of stake to refund
+     */
+    pub fn claim_unbonded_for(&mut self, account_id: &AccountId) -> Balance {
+        // the outcomes are kept so `prune_participant` can find the stake left on an incorrect bonded outcome
+        let staked_outcomes = match self.user_to_outcomes.get(account_id) {
+            Some(staked_outcomes) => staked_outcomes,
+            None => return 0
+        };
+        let mut outcome_to_stake = match self.user_to_outcome_to_stake.get(account_id) {
+            Some(outcome_to_stake) => outcome_to_stake,
+            None => return 0
+        };
+
+        staked_outcomes
+            .iter()
+            .filter(|outcome| !self.is_in_bonded_bucket(outcome))
+            .map(|outcome| outcome_to_stake.remove(outcome).unwrap_or(0))
+            .sum()
+    }
+
+    pub fn total_unrevealed(&self) -> Balance {
+        self.total_committed - self.total_revealed
+    }
+
+    // @returns whether this window bonded on `final_outcome`, or an outcome within its tolerance
+    pub fn is_correct(&self, final_outcome: &Outcome, tolerance: Option<&NumberTolerance>) -> bool {
+        match (&self.bonded_outcome, tolerance) {
+            (Some(bonded_outcome), Some(tolerance)) => tolerance.contains(bonded_outcome, final_outcome),
+            (Some(bonded_outcome), None) => bonded_outcome == final_outcome,
+            (None, _) => false
+        }
+    }
+
+    pub fn claim_for(&mut self, account_id: AccountId, final_outcome: &Outcome, tolerance: Option<&NumberTolerance>) -> WindowStakeResult {
         // Check if there is a bonded outcome, if there is none it means it can be ignored in payout calc since it can only be the final unsuccessful window
         match &self.bonded_outcome {
             Some(bonded_outcome) => {
+                let is_correct = self.is_correct(final_outcome, tolerance);
                 // If the bonded outcome for this window is equal to the finalized outcome the user's stake in this window and the total amount staked should be returned (which == `self.bond_size`)
-                if bonded_outcome == final_outcome {
+                if is_correct {
+                    // With a tolerance the user's stake is spread over every answer in the bonded bucket
+                    let bonded_bucket = self.bonded_bucket.clone().unwrap_or_else(|| bonded_outcome.clone());
+                    let bonded_members = match tolerance {
+                        Some(_) => self.bucket_to_members.get(&bonded_bucket).unwrap_or_else(|| vec![bonded_bucket.clone()]),
+                        None => vec![bonded_bucket]
+                    };
                     WindowStakeResult::Correct(CorrectStake {
                         bonded_stake: self.bond_size,
                         // Get the users stake in this outcome for this window
                         user_stake:  match &mut self.user_to_outcome_to_stake.get(&account_id) {
                             Some(outcome_to_stake) => {
-                                outcome_to_stake.remove(&bonded_outcome).unwrap_or(0)
+                                bonded_members.iter().map(|member| outcome_to_stake.remove(member).unwrap_or(0)).sum()
                             },
                             None => 0
                         }
@@ -140,4 +363,35 @@ impl ResolutionWindow {
             None => WindowStakeResult::NoResult // Return `NoResult` for non-bonded window
         }
     }
-}
\ No newline at end of file
+
+    // @notice Removes the stake entries of `account_id` in this window, only used when archiving a settled `DataRequest`
+    pub fn prune_participant(&mut self, account_id: &AccountId) {
+        let staked_outcomes = self.user_to_outcomes.remove(account_id).unwrap_or_default();
+        if let Some(mut outcome_to_stake) = self.user_to_outcome_to_stake.remove(account_id) {
+            for outcome in &staked_outcomes {
+                outcome_to_stake.remove(outcome);
+            }
+        }
+        self.user_to_commitment.remove(account_id);
+    }
+
+    /**
+     * @notice Removes the entries of up to `limit` staked outcomes, only used when archiving a settled `DataRequest`
+     * @returns the amount of outcomes that were pruned
+     */
+    pub fn prune_outcomes(&mut self, limit: u64, tolerance: Option<&NumberTolerance>) -> u64 {
+        let outcomes: Vec<Outcome> = self.staked_outcomes.iter().take(limit as usize).collect();
+        for outcome in &outcomes {
+            self.outcome_to_stake.remove(outcome);
+            self.member_to_stake.remove(outcome);
+            self.outcome_to_bucket.remove(outcome);
+            self.bucket_to_members.remove(outcome);
+            if let (Some(_), Some(value)) = (tolerance, outcome.number_value()) {
+                self.anchors.remove(&value);
+            }
+            self.staked_outcomes.remove(outcome);
+        }
+        outcomes.len() as u64
+    }
+}
+
@@ -1,5 +1,5 @@
 use super::*;
-use near_sdk::{Promise};
+use near_sdk::{Promise, StorageUsage};
 use near_sdk::json_types::{ValidAccountId, U128};
 use near_sdk::serde::Serialize;
 
@@ -133,6 +133,14 @@ impl Contract {
             self.accounts.insert(sender_id, &account);
         }
     }
+
+    // @notice Credits storage that `account_id` paid for, and that's freed by another account, to its storage balance
+    pub fn release_storage(&mut self, account_id: &AccountId, freed_storage: StorageUsage) {
+        let mut account = self.get_storage_account(account_id);
+        account.available += u128::from(freed_storage) * STORAGE_PRICE_PER_BYTE;
+
+        self.accounts.insert(account_id, &account);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -177,7 +185,9 @@ mod mock_token_basic_tests {
             interface_name: account.clone(),
             account_id: account.clone(),
             stake_multiplier: None,
-            code_base_url: None
+            code_base_url: None,
+            callback: None,
+            overrides: None
         }
     }
 
@@ -192,6 +202,17 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(250),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
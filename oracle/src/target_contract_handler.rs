@@ -1,40 +1,57 @@
-// use crate::*;
-// use near_sdk::json_types::{ U64 };
-// use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
-// use near_sdk::serde::{ Deserialize, Serialize };
-// use near_sdk::{ AccountId, Gas, ext_contract, Promise };
-// use types::Outcome;
+use crate::*;
+use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
+use near_sdk::serde::{ Deserialize, Serialize };
+use near_sdk::serde_json::json;
+use near_sdk::{ AccountId, Gas, Promise };
 
-#[ext_contract]
-pub trait TargetContractExtern {
-    fn set_outcome(request_id: U64, requestor: AccountId, outcome: Outcome, tags: Option<Vec<String>>, final_arbitrator_triggered: bool);
-}
+pub const MAX_TARGET_CONTRACT_GAS: Gas = 100_000_000_000_000;
 
-// #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
-// pub struct TargetContract(pub AccountId);
+/**
+ * @notice Contract that receives the outcome of a `DataRequest` instead of, or next to, the requestor
+ */
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct TargetContract {
+    pub account_id: AccountId,
+    pub method_name: String, // called with the same arguments as the requestor's `set_outcome`
+    pub gas: U64,
+    pub also_notify_requestor: bool,
+}
 
-const GAS_BASE_SET_OUTCOME: Gas = 250_000_000_000_000;
+impl TargetContract {
+    pub fn assert_valid(&self) {
+        assert!(env::is_valid_account_id(self.account_id.as_bytes()), "Invalid target contract account id: {}", self.account_id);
+        assert!(!self.method_name.is_empty(), "Target contract method name can't be empty");
+        assert!(
+            u64::from(self.gas) > 0 && u64::from(self.gas) <= MAX_TARGET_CONTRACT_GAS,
+            "Target contract gas should be between 1 and {}",
+            MAX_TARGET_CONTRACT_GAS
+        );
+    }
 
-// impl TargetContract {
     pub fn set_outcome(
         &self,
         request_id: U64,
         requestor: AccountId,
         outcome: Outcome,
         tags: Option<Vec<String>>,
-        final_arbitrator_triggered: bool
+        final_arbitrator_triggered: bool,
+        arbitration_fallback_used: bool,
+        gas: Gas
     ) -> Promise {
-        target_contract_extern::set_outcome(
-            request_id,
-            requestor,
-            outcome,
-            tags,
-            final_arbitrator_triggered,
+        let args = json!({
+            "request_id": request_id,
+            "requestor": requestor,
+            "outcome": outcome,
+            "tags": tags,
+            "final_arbitrator_triggered": final_arbitrator_triggered,
+            "arbitration_fallback_used": arbitration_fallback_used,
+        });
 
-            // NEAR params
-            &self.0,
-            1, 
-            GAS_BASE_SET_OUTCOME / 10,
+        Promise::new(self.account_id.to_string()).function_call(
+            self.method_name.as_bytes().to_vec(),
+            args.to_string().into_bytes(),
+            1,
+            gas
         )
     }
-// }
+}
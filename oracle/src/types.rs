@@ -20,6 +20,14 @@ pub struct AnswerNumberType {
     pub negative: bool,
 }
 
+impl AnswerNumberType {
+    // @returns the signed value, values that don't fit an i128 are saturated
+    pub fn to_signed(&self) -> i128 {
+        let value = if self.value.0 > i128::MAX as u128 { i128::MAX } else { self.value.0 as i128 };
+        if self.negative { -value } else { value }
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Debug, PartialEq, Clone)]
 pub enum AnswerType {
     Number(AnswerNumberType),
@@ -31,3 +39,13 @@ pub enum Outcome {
     Answer(AnswerType),
     Invalid
 }
+
+
+impl Outcome {
+    pub fn number_value(&self) -> Option<i128> {
+        match self {
+            Outcome::Answer(AnswerType::Number(number)) => Some(number.to_signed()),
+            _ => None
+        }
+    }
+}
\ No newline at end of file
@@ -1,5 +1,10 @@
 use crate::*;
-use near_sdk::{ init };
+use near_sdk::serde::{ Deserialize, Serialize };
+use crate::data_request::{ DataRequestConfig, DataRequestDataType };
+use crate::fee_config::FeeConfig;
+use crate::oracle_config::{ ArbitrationFallback, BondEscalation, OracleConfig };
+use crate::requestor_handler::OutcomeDelivery;
+use crate::resolution_window::ResolutionWindow;
 
 #[cfg(target_arch = "wasm32")]
 mod upgrade {
@@ -20,7 +25,7 @@ mod upgrade {
         env::setup_panic_hook();
         env::set_blockchain_interface(Box::new(near_blockchain::NearBlockchain {}));
         let contract: Contract = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
-        contract.assert_gov();
+        contract.assert_role(governance::Role::Upgrader);
         let current_id = env::current_account_id().into_bytes();
         let method_name = "migrate".as_bytes().to_vec();
 
@@ -58,10 +63,489 @@ mod upgrade {
     }
 }
 
+// parameters the baseline config didn't have, gov can change them through `set_config` after the upgrade
+const LEGACY_MAX_SETTLEMENT_TIME_OFFSET: Duration = 0;
+const LEGACY_REQUEST_EXPIRY_DURATION: Duration = 2_592_000_000_000_000; // 30 days
+const LEGACY_BOND_MULTIPLIER: u32 = 20_000; // baseline bonds doubled every round
+const LEGACY_CLAIM_WINDOW_DURATION: Duration = 2_592_000_000_000_000; // 30 days
+
+/**
+ * @notice Layout of the baseline contract, its collections are read with the baseline element types below and converted in `migrate`
+ */
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ContractV1 {
+    pub whitelist: Option<LookupMap<AccountId, RequestorV1>>,
+    pub configs: Vector<OracleConfigV1>,
+    pub data_requests: Vector<DataRequestV1>,
+    pub accounts: LookupMap<AccountId, AccountStorageBalance>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+pub struct RequestorV1 {
+    pub interface_name: String,
+    pub account_id: AccountId,
+    pub stake_multiplier: Option<u16>,
+    pub code_base_url: Option<String>,
+}
+
+impl From<RequestorV1> for Requestor {
+    fn from(requestor: RequestorV1) -> Self {
+        Self {
+            interface_name: requestor.interface_name,
+            account_id: requestor.account_id,
+            stake_multiplier: requestor.stake_multiplier,
+            code_base_url: requestor.code_base_url,
+            callback: None,
+            overrides: None,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct OracleConfigV1 {
+    pub gov: AccountId,
+    pub final_arbitrator: AccountId,
+    pub stake_token: AccountId,
+    pub payment_token: AccountId,
+    pub validity_bond: U128,
+    pub max_outcomes: u8,
+    pub default_challenge_window_duration: WrappedTimestamp,
+    pub min_initial_challenge_window_duration: WrappedTimestamp,
+    pub final_arbitrator_invoke_amount: U128,
+    pub fee: FeeConfig,
+}
+
+impl From<OracleConfigV1> for OracleConfig {
+    fn from(config: OracleConfigV1) -> Self {
+        Self {
+            treasury: config.gov.to_string(),
+            gov: config.gov,
+            final_arbitrator: config.final_arbitrator,
+            stake_token: config.stake_token,
+            payment_token: config.payment_token,
+            validity_bond: config.validity_bond,
+            max_outcomes: config.max_outcomes,
+            default_challenge_window_duration: config.default_challenge_window_duration,
+            min_initial_challenge_window_duration: config.min_initial_challenge_window_duration,
+            final_arbitrator_invoke_amount: config.final_arbitrator_invoke_amount,
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(LEGACY_MAX_SETTLEMENT_TIME_OFFSET),
+            request_expiry_duration: U64(LEGACY_REQUEST_EXPIRY_DURATION),
+            bond_escalation: BondEscalation::Multiplier(LEGACY_BOND_MULTIPLIER),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(LEGACY_CLAIM_WINDOW_DURATION),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
+            fee: config.fee,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct DataRequestV1 {
+    pub id: u64,
+    pub description: Option<String>,
+    pub sources: Vec<Source>,
+    pub outcomes: Option<Vec<String>>,
+    pub requestor: RequestorV1,
+    pub creator: AccountId,
+    pub finalized_outcome: Option<Outcome>,
+    pub resolution_windows: Vector<ResolutionWindowV1>,
+    pub global_config_id: u64,
+    pub request_config: DataRequestConfigV1,
+    pub initial_challenge_period: Duration,
+    pub final_arbitrator_triggered: bool,
+    pub tags: Option<Vec<String>>,
+    pub data_type: DataRequestDataType,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct DataRequestConfigV1 {
+    pub default_challenge_window_duration: Duration,
+    pub final_arbitrator_invoke_amount: Balance,
+    pub final_arbitrator: AccountId,
+    pub validity_bond: Balance,
+    pub paid_fee: Balance,
+    pub stake_multiplier: Option<u16>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolutionWindowV1 {
+    pub dr_id: u64,
+    pub round: u16,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub bond_size: Balance,
+    pub outcome_to_stake: LookupMap<Outcome, Balance>,
+    pub user_to_outcome_to_stake: LookupMap<AccountId, LookupMap<Outcome, Balance>>,
+    pub bonded_outcome: Option<Outcome>,
+}
+
+impl From<ResolutionWindowV1> for ResolutionWindow {
+    // the stake maps keep their baseline prefixes, so only the fields added since are initialised
+    fn from(window: ResolutionWindowV1) -> Self {
+        let mut resolution_window = ResolutionWindow::create(
+            window.dr_id,
+            window.round,
+            window.bond_size,
+            window.end_time - window.start_time,
+            None,
+            window.start_time
+        );
+        resolution_window.bonded_bucket = window.bonded_outcome.clone();
+        resolution_window.bonded_outcome = window.bonded_outcome;
+        resolution_window
+    }
+}
+
+impl From<DataRequestV1> for DataRequest {
+    /**
+     * @notice The baseline didn't track balances, claims or stakers. Bonded stake is the only stake claims pay out so it's
+     * the `stake_balance`, and claims never close since it's unknown what was claimed already
+     */
+    fn from(dr: DataRequestV1) -> Self {
+        let legacy_windows: Vec<ResolutionWindowV1> = dr.resolution_windows.to_vec();
+        let mut resolution_windows = Vector::new(format!("rw{}", dr.id).as_bytes().to_vec());
+        let mut stake_balance = 0;
+        for window in legacy_windows {
+            if window.bonded_outcome.is_some() {
+                stake_balance += window.bond_size;
+            }
+            resolution_windows.push(&ResolutionWindow::from(window));
+        }
+        let finalized = dr.finalized_outcome.is_some();
+
+        Self {
+            id: dr.id,
+            description: dr.description,
+            sources: dr.sources,
+            outcomes: dr.outcomes,
+            requestor: dr.requestor.into(),
+            creator: dr.creator,
+            finalized_outcome: dr.finalized_outcome,
+            resolution_windows,
+            global_config_id: dr.global_config_id,
+            request_config: DataRequestConfig {
+                default_challenge_window_duration: dr.request_config.default_challenge_window_duration,
+                final_arbitrator_invoke_amount: dr.request_config.final_arbitrator_invoke_amount,
+                final_arbitrator: dr.request_config.final_arbitrator,
+                validity_bond: dr.request_config.validity_bond,
+                paid_fee: dr.request_config.paid_fee,
+                stake_multiplier: dr.request_config.stake_multiplier,
+                slash_unrevealed_stake: false,
+                request_expiry_duration: LEGACY_REQUEST_EXPIRY_DURATION,
+                bond_escalation: BondEscalation::Multiplier(LEGACY_BOND_MULTIPLIER),
+                arbitration_council: None,
+                arbitration_timeout: 0,
+                arbitration_fallback: ArbitrationFallback::LastBondedOutcome,
+                claim_window_duration: u64::MAX,
+            },
+            initial_challenge_period: dr.initial_challenge_period,
+            final_arbitrator_triggered: dr.final_arbitrator_triggered,
+            tags: dr.tags,
+            data_type: dr.data_type,
+            reveal_period: None,
+            tolerance: None,
+            cancelled: false,
+            settlement_time: None,
+            created_at: env::block_timestamp(),
+            expiry_fallback: None,
+            expired: false,
+            arbitration_votes: Vec::new(),
+            final_arbitrator_triggered_at: if dr.final_arbitrator_triggered { Some(env::block_timestamp()) } else { None },
+            arbitration_fallback_used: false,
+            outcome_delivery: if finalized { Some(OutcomeDelivery::Delivered) } else { None },
+            undelivered_recipients: Vec::new(),
+            target_contract: None,
+            finalized_at: if finalized { Some(env::block_timestamp()) } else { None },
+            participants: UnorderedSet::new(format!("drp{}", dr.id).as_bytes().to_vec()),
+            claimed_correct_stake: 0,
+            archived: false,
+            // the validity bond is returned on finalization
+            payment_balance: dr.request_config.paid_fee + if finalized { 0 } else { dr.request_config.validity_bond },
+            stake_balance,
+            swept: false,
+        }
+    }
+}
+
+#[near_bindgen]
 impl Contract {
+    /**
+     * @notice Converts the state of a baseline contract, the configs are rewritten in place, the `DataRequest`s are moved over
+     * through `migrate_data_requests` and the whitelist through `migrate_whitelist`. Collections added since the baseline start out empty
+     */
     #[init(ignore_state)]
+    #[private]
     pub fn migrate() -> Self {
-        let contract: Contract = env::state_read().expect("ERR_NOT_INITIALIZED");
-        contract
+        let old: ContractV1 = env::state_read().expect("ERR_NOT_INITIALIZED");
+
+        let mut configs = Vector::new(b"c".to_vec());
+        for config in old.configs.to_vec() {
+            configs.push(&OracleConfig::from(config));
+        }
+
+        Self {
+            whitelist: whitelist::Whitelist::from_legacy(old.whitelist),
+            configs,
+            data_requests: Vector::new(b"dr".to_vec()),
+            accounts: old.accounts,
+            recurring_requests: Vector::new(b"rr".to_vec()),
+            account_positions: LookupMap::new(b"ap".to_vec()),
+            requests_by_requestor: LookupMap::new(b"rbr".to_vec()),
+            requests_by_creator: LookupMap::new(b"rbc".to_vec()),
+            requests_by_tag: LookupMap::new(b"rbt".to_vec()),
+            request_states: TreeMap::new(b"rs".to_vec()),
+            failed_deliveries: UnorderedSet::new(b"fd".to_vec()),
+            pending_config: None,
+            pending_gov: None,
+            pause_state: pause::PauseState::default(),
+            claim_pauses: pause::ClaimPauses::default(),
+            roles: LookupMap::new(b"ro".to_vec()),
+            legacy_data_requests: if old.data_requests.is_empty() { None } else { Some(old.data_requests) },
+        }
+    }
+
+    /**
+     * @notice Converts the next `limit` baseline `DataRequest`s, new requests can't be created until all of them are converted.
+     * Callable by gov or accounts with the `Upgrader` role, the caller pays for the storage the new fields take up
+     * @returns whether every `DataRequest` is converted
+     */
+    #[payable]
+    pub fn migrate_data_requests(&mut self, limit: U64) -> bool {
+        self.assert_role(governance::Role::Upgrader);
+        let legacy_data_requests = self.legacy_data_requests.take().expect("No `DataRequest`s left to migrate");
+
+        let initial_storage = env::storage_usage();
+        let from_index = self.data_requests.len();
+        let to_index = std::cmp::min(from_index.saturating_add(limit.into()), legacy_data_requests.len());
+
+        // both vectors share their prefix, every index is read before it's overwritten by the converted `DataRequest`
+        for index in from_index..to_index {
+            let dr = DataRequest::from(legacy_data_requests.get(index).unwrap());
+            self.data_requests.push(&dr);
+            self.index_new_request(&dr);
+        }
+
+        let migrated = to_index == legacy_data_requests.len();
+        if !migrated {
+            self.legacy_data_requests = Some(legacy_data_requests);
+        }
+
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+        migrated
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use near_sdk::{ MockedBlockchain };
+    use near_sdk::{ testing_env, VMContext };
+    use super::*;
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn bob() -> AccountId {
+        "bob.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn gov() -> AccountId {
+        "gov.near".to_string()
+    }
+
+    fn answer(value: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(value.to_string()))
+    }
+
+    fn legacy_requestor(account_id: AccountId) -> RequestorV1 {
+        RequestorV1 {
+            interface_name: account_id.clone(),
+            account_id,
+            stake_multiplier: None,
+            code_base_url: None,
+        }
+    }
+
+    fn legacy_config() -> OracleConfigV1 {
+        OracleConfigV1 {
+            gov: gov(),
+            final_arbitrator: alice(),
+            payment_token: token(),
+            stake_token: token(),
+            validity_bond: U128(100),
+            max_outcomes: 8,
+            default_challenge_window_duration: U64(1000),
+            min_initial_challenge_window_duration: U64(1000),
+            final_arbitrator_invoke_amount: U128(250),
+            fee: FeeConfig {
+                flux_market_cap: U128(50000),
+                total_value_staked: U128(10000),
+                resolution_fee_percentage: 5000,
+            },
+        }
+    }
+
+    fn legacy_window(round: u16, bond_size: Balance, bonded_outcome: Option<Outcome>) -> ResolutionWindowV1 {
+        let mut outcome_to_stake = LookupMap::new(format!("ots0:{}", round).as_bytes().to_vec());
+        if let Some(outcome) = &bonded_outcome {
+            outcome_to_stake.insert(outcome, &bond_size);
+        }
+
+        ResolutionWindowV1 {
+            dr_id: 0,
+            round,
+            start_time: u64::from(round) * 1000,
+            end_time: u64::from(round) * 1000 + 1000,
+            bond_size,
+            outcome_to_stake,
+            user_to_outcome_to_stake: LookupMap::new(format!("utots0:{}", round).as_bytes().to_vec()),
+            bonded_outcome,
+        }
+    }
+
+    // writes the state of a baseline contract with a whitelisted requestor and a disputed `DataRequest`
+    fn write_baseline_state() {
+        let mut whitelist = LookupMap::new(b"wlr".to_vec());
+        whitelist.insert(&bob(), &legacy_requestor(bob()));
+
+        let mut configs = Vector::new(b"c".to_vec());
+        configs.push(&legacy_config());
+
+        let mut resolution_windows = Vector::new(b"rw0".to_vec());
+        resolution_windows.push(&legacy_window(0, 200, Some(answer("a"))));
+        resolution_windows.push(&legacy_window(1, 400, None));
+
+        let mut data_requests = Vector::new(b"dr".to_vec());
+        data_requests.push(&DataRequestV1 {
+            id: 0,
+            description: Some("a".to_string()),
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+            requestor: legacy_requestor(bob()),
+            creator: bob(),
+            finalized_outcome: None,
+            resolution_windows,
+            global_config_id: 0,
+            request_config: DataRequestConfigV1 {
+                default_challenge_window_duration: 1000,
+                final_arbitrator_invoke_amount: 250,
+                final_arbitrator: alice(),
+                validity_bond: 100,
+                paid_fee: 0,
+                stake_multiplier: None,
+            },
+            initial_challenge_period: 1000,
+            final_arbitrator_triggered: false,
+            tags: Some(vec!["tag".to_string()]),
+            data_type: DataRequestDataType::String,
+        });
+
+        env::state_write(&ContractV1 {
+            whitelist: Some(whitelist),
+            configs,
+            data_requests,
+            accounts: LookupMap::new(b"a".to_vec()),
+        });
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: token(),
+            signer_account_id: bob(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 1000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 10u128.pow(24),
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    #[test]
+    fn migrate_baseline_state() {
+        testing_env!(get_context(gov()));
+        write_baseline_state();
+        let mut contract = Contract::migrate();
+
+        let config = contract.get_config();
+        assert_eq!(config.gov, gov());
+        assert_eq!(config.treasury, gov());
+        assert_eq!(config.bond_escalation, BondEscalation::Multiplier(20_000));
+
+        let requestor = contract.whitelist.get(&bob()).unwrap();
+        assert_eq!(requestor.account_id, bob());
+        assert!(requestor.callback.is_none());
+
+        assert!(contract.get_request_by_id(U64(0)).is_none());
+        assert!(contract.migrate_data_requests(U64(10)));
+        assert!(contract.legacy_data_requests.is_none());
+
+        let request = contract.get_request_by_id(U64(0)).unwrap();
+        assert_eq!(request.requestor.account_id, bob());
+        assert_eq!(request.resolution_windows.len(), 2);
+        assert_eq!(request.stake_balance, U128(200));
+        assert_eq!(request.payment_balance, U128(100));
+        assert!(request.finalized_at.is_none());
+
+        // stake from before the upgrade is kept under the baseline prefixes
+        let window = contract.data_requests.get(0).unwrap().resolution_windows.get(0).unwrap();
+        assert_eq!(window.outcome_to_stake.get(&answer("a")), Some(200));
+        assert_eq!(window.bonded_bucket, Some(answer("a")));
+
+        assert_eq!(contract.requests_by_requestor.get(&bob()).unwrap().to_vec(), vec![0]);
+        assert_eq!(contract.requests_by_tag.get(&"tag".to_string()).unwrap().to_vec(), vec![0]);
+        assert_eq!(contract.failed_deliveries.len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "`DataRequest`s are still being migrated")]
+    fn dr_new_before_data_requests_migrated() {
+        testing_env!(get_context(gov()));
+        write_baseline_state();
+        let mut contract = Contract::migrate();
+
+        testing_env!(get_context(token()));
+        contract.dr_new(bob(), 100, NewDataRequestArgs {
+            sources: Vec::new(),
+            outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+            challenge_period: U64(1500),
+            description: Some("a".to_string()),
+            tags: None,
+            data_type: DataRequestDataType::String,
+            creator: bob(),
+            reveal_period: None,
+            tolerance: None,
+            settlement_time: None,
+            expiry_fallback: None,
+            target_contract: None,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn migrate_data_requests_from_user() {
+        testing_env!(get_context(gov()));
+        write_baseline_state();
+        let mut contract = Contract::migrate();
+
+        testing_env!(get_context(alice()));
+        contract.migrate_data_requests(U64(10));
     }
 }
@@ -2,14 +2,18 @@ use crate::*;
 
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::AccountId;
-use near_sdk::collections::LookupMap;
-   
+use near_sdk::collections::{ LookupMap, UnorderedMap };
+use crate::upgrade::RequestorV1;
+
+const LEGACY_WHITELIST_PREFIX: &[u8] = b"wlr";
+const WHITELIST_PREFIX: &[u8] = b"wlu";
+
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct Whitelist(Option<LookupMap<AccountId, Requestor>>); // maps requestor account id to requestors config
+pub struct Whitelist(Option<UnorderedMap<AccountId, Requestor>>); // maps requestor account id to requestors config
 
 impl Whitelist {
     pub fn new(initial_whitelist: Option<Vec<Requestor>>) -> Self {
-        let mut whitelist: LookupMap<AccountId, Requestor> = LookupMap::new(b"wlr".to_vec());
+        let mut whitelist: UnorderedMap<AccountId, Requestor> = UnorderedMap::new(WHITELIST_PREFIX.to_vec());
 
         match initial_whitelist {
             Some(initial_whitelist) => {
@@ -24,17 +28,35 @@ impl Whitelist {
         }
     }
 
+    /**
+     * @notice Replaces the non enumerable whitelist of contracts deployed before the whitelist could be listed, the legacy
+     * entries are still found by `get` until they're moved over with `migrate_whitelist`
+     */
+    pub fn from_legacy(legacy_whitelist: Option<LookupMap<AccountId, RequestorV1>>) -> Self {
+        Self(legacy_whitelist.map(|_| UnorderedMap::new(WHITELIST_PREFIX.to_vec())))
+    }
+
+    // entries keep the baseline `Requestor` layout until they're migrated
+    fn legacy() -> LookupMap<AccountId, RequestorV1> {
+        LookupMap::new(LEGACY_WHITELIST_PREFIX.to_vec())
+    }
+
+    pub fn get(&self, requestor: &AccountId) -> Option<Requestor> {
+        self.0
+            .as_ref()
+            .expect("No whitelist initiated")
+            .get(requestor)
+            .or_else(|| Self::legacy().get(requestor).map(Requestor::from))
+    }
+
     pub fn contains(&self, requestor: AccountId) -> bool {
-        match self.0.as_ref().expect("No whitelist initiated").get(&requestor) {
-            None => false,
-            _ => true
-        }
+        self.get(&requestor).is_some()
     }
 
     pub fn get_stake_multiplier(&self, requestor: &AccountId) -> Option<u16> {
         match &self.0 {
-            Some(whitelist) => {
-                whitelist.get(requestor).expect("not whitelisted").stake_multiplier
+            Some(_) => {
+                self.get(requestor).expect("not whitelisted").stake_multiplier
             },
             None => None
         }
@@ -42,18 +64,49 @@ impl Whitelist {
 
     pub fn whitelist_get_expect(&self, requestor: &AccountId) -> Requestor {
         match &self.0 {
-            Some(whitelist) => {
-                whitelist.get(requestor).expect("requestor not whitelisted")
+            Some(_) => {
+                self.get(requestor).expect("requestor not whitelisted")
             }, 
             None => Requestor::new_no_whitelist(requestor)
         }
     }
+
+    fn insert(&mut self, requestor: &Requestor) {
+        self.0
+            .get_or_insert_with(|| UnorderedMap::new(WHITELIST_PREFIX.to_vec()))
+            .insert(&requestor.account_id, requestor);
+    }
+
+    // @returns the removed `Requestor`
+    fn remove(&mut self, requestor: &AccountId) -> Requestor {
+        let whitelist = self.0.as_mut().expect("Uninitiated whitelist");
+        let removed = whitelist.remove(requestor);
+        let legacy_removed = Self::legacy().remove(requestor).map(Requestor::from);
+        removed.or(legacy_removed).expect("requestor not whitelisted")
+    }
+}
+
+fn assert_valid_requestor(requestor: &Requestor, config: &oracle_config::OracleConfig) {
+    match requestor.stake_multiplier {
+        Some(m) => assert!(m > 0, "stake multiplier can't be 0"),
+        _ => ()
+    };
+    if let Some(callback) = &requestor.callback {
+        callback.assert_valid();
+    }
+    if let Some(overrides) = &requestor.overrides {
+        config.requestor_bounds.as_ref().expect("Requestor overrides aren't enabled").assert_within(overrides);
+    }
 }
 
 trait WhitelistHandler {
     fn add_to_whitelist(&mut self, new_requestor: Requestor);
-    fn remove_from_whitelist(&mut self, requestor: Requestor);
+    fn update_requestor(&mut self, requestor: Requestor);
+    fn remove_from_whitelist(&mut self, requestor: AccountId);
+    fn migrate_whitelist(&mut self, account_ids: Vec<AccountId>);
     fn whitelist_contains(&self, requestor: AccountId) -> bool;
+    fn get_requestor(&self, account_id: AccountId) -> Option<Requestor>;
+    fn get_whitelist(&self, from_index: U64, limit: U64) -> Vec<Requestor>;
 }
 
 #[near_bindgen]
@@ -61,54 +114,89 @@ impl WhitelistHandler for Contract {
     
     #[payable]
     fn add_to_whitelist(&mut self, new_requestor: Requestor) {
-        self.assert_gov();
-
-
-        match new_requestor.stake_multiplier {
-            Some(m) => assert!(m > 0, "stake multiplier can't be 0"),
-            _ => ()
-        };
+        self.assert_role(governance::Role::WhitelistAdmin);
+        assert_valid_requestor(&new_requestor, &self.get_config());
 
         let initial_storage = env::storage_usage();
 
-        match &mut self.whitelist.0 {
-            Some(whitelist) => {
-                whitelist.insert(&new_requestor.account_id, &new_requestor);
-            }, 
-            None => {
-                let mut whitelist: LookupMap<AccountId, Requestor> = LookupMap::new(b"wlr".to_vec());
-                whitelist.insert(&new_requestor.account_id, &new_requestor);
-                self.whitelist = Whitelist(Some(whitelist));
-            }
-        };
+        self.whitelist.insert(&new_requestor);
       
         logger::log_whitelist(&new_requestor, true);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
     }
 
+    /**
+     * @notice Edits the config of a whitelisted requestor, e.g. its `stake_multiplier` or `code_base_url`
+     */
     #[payable]
-    fn remove_from_whitelist(&mut self, requestor: Requestor) {
-        self.assert_gov();
+    fn update_requestor(&mut self, requestor: Requestor) {
+        self.assert_role(governance::Role::WhitelistAdmin);
+        assert_valid_requestor(&requestor, &self.get_config());
+        assert!(self.whitelist.contains(requestor.account_id.to_string()), "requestor not whitelisted");
 
         let initial_storage = env::storage_usage();
 
+        self.whitelist.insert(&requestor);
+
+        logger::log_whitelist(&requestor, true);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
-        logger::log_whitelist(&requestor, false);
+    }
 
+    #[payable]
+    fn remove_from_whitelist(&mut self, requestor: AccountId) {
+        self.assert_role(governance::Role::WhitelistAdmin);
 
-        match &mut self.whitelist.0 {
-            Some(whitelist) => {
-                whitelist.remove(&requestor.account_id);
-            }, 
-            None => {
-                panic!("Uninitiated whitelist")
+        let initial_storage = env::storage_usage();
+
+        let removed = self.whitelist.remove(&requestor);
+
+        logger::log_whitelist(&removed, false);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    /**
+     * @notice Moves requestors from the legacy whitelist into the enumerable whitelist, the legacy whitelist can't be
+     * iterated so the account ids have to be provided
+     */
+    #[payable]
+    fn migrate_whitelist(&mut self, account_ids: Vec<AccountId>) {
+        self.assert_role(governance::Role::WhitelistAdmin);
+
+        let initial_storage = env::storage_usage();
+        let mut legacy = Whitelist::legacy();
+
+        for account_id in account_ids {
+            if let Some(requestor) = legacy.remove(&account_id) {
+                self.whitelist.insert(&requestor.into());
             }
-        };
+        }
+
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
     }
 
     fn whitelist_contains(&self, requestor: AccountId) -> bool {
         self.whitelist.contains(requestor)
     }
+
+    fn get_requestor(&self, account_id: AccountId) -> Option<Requestor> {
+        match &self.whitelist.0 {
+            Some(_) => self.whitelist.get(&account_id),
+            None => None
+        }
+    }
+
+    fn get_whitelist(&self, from_index: U64, limit: U64) -> Vec<Requestor> {
+        let whitelist = match &self.whitelist.0 {
+            Some(whitelist) => whitelist,
+            None => return Vec::new()
+        };
+        let requestors = whitelist.values_as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i.saturating_add(limit.into()), requestors.len()))
+            .map(|index| requestors.get(index).unwrap())
+            .collect()
+    }
 }
 
 impl Contract {
@@ -131,6 +219,7 @@ mod mock_token_basic_tests {
     use near_sdk::{ MockedBlockchain };
     use near_sdk::{ testing_env, VMContext };
     use fee_config::FeeConfig;
+    use crate::requestor_handler::{ RequestorCallback, RequestorOverrides };
     use super::*;
 
     fn alice() -> AccountId {
@@ -158,7 +247,9 @@ mod mock_token_basic_tests {
             interface_name: account.clone(),
             account_id: account.clone(),
             stake_multiplier: None,
-            code_base_url: None
+            code_base_url: None,
+            callback: None,
+            overrides: None
         }
     }
 
@@ -173,6 +264,17 @@ mod mock_token_basic_tests {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: oracle_config::BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: oracle_config::ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "treasury.near".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),
@@ -224,7 +326,7 @@ mod mock_token_basic_tests {
         assert!(!contract.whitelist_contains(alice()));
         contract.add_to_whitelist(registry_entry(alice()));
         assert!(contract.whitelist_contains(alice()));
-        contract.remove_from_whitelist(registry_entry(alice()));
+        contract.remove_from_whitelist(alice());
         assert!(!contract.whitelist_contains(alice()));
     }
 
@@ -251,6 +353,169 @@ mod mock_token_basic_tests {
         testing_env!(get_context(alice()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
-        contract.remove_from_whitelist(registry_entry(alice()));
+        contract.remove_from_whitelist(alice());
+    }
+
+    fn callback(gas: u64) -> RequestorCallback {
+        RequestorCallback {
+            method_name: "on_oracle_outcome".to_string(),
+            gas: U64(gas),
+            deposit: U128(0),
+            include_tags: false,
+        }
+    }
+
+    #[test]
+    fn whitelist_add_with_callback() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config());
+
+        let mut requestor = registry_entry(alice());
+        requestor.callback = Some(callback(50_000_000_000_000));
+        contract.add_to_whitelist(requestor);
+        assert_eq!(contract.whitelist.whitelist_get_expect(&alice()).callback, Some(callback(50_000_000_000_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Callback gas should be between 1 and 100000000000000")]
+    fn whitelist_add_callback_too_much_gas() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config());
+
+        let mut requestor = registry_entry(alice());
+        requestor.callback = Some(callback(100_000_000_000_001));
+        contract.add_to_whitelist(requestor);
+    }
+
+    #[test]
+    fn whitelist_update_requestor() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+
+        let mut requestor = registry_entry(bob());
+        requestor.stake_multiplier = Some(2);
+        requestor.code_base_url = Some("https://example.com".to_string());
+        contract.update_requestor(requestor);
+
+        let stored = contract.get_requestor(bob()).unwrap();
+        assert_eq!(stored.stake_multiplier, Some(2));
+        assert_eq!(stored.code_base_url, Some("https://example.com".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "requestor not whitelisted")]
+    fn whitelist_update_unknown_requestor() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.update_requestor(registry_entry(alice()));
+    }
+
+    #[test]
+    fn whitelist_get_requestor() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let contract = Contract::new(whitelist, config());
+        assert_eq!(contract.get_requestor(bob()).unwrap().account_id, bob());
+        assert!(contract.get_requestor(alice()).is_none());
+
+        let contract = Contract::new(None, config());
+        assert!(contract.get_requestor(bob()).is_none());
+    }
+
+    #[test]
+    fn whitelist_pagination() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(alice()), registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+
+        let page: Vec<AccountId> = contract.get_whitelist(U64(0), U64(2)).into_iter().map(|r| r.account_id).collect();
+        assert_eq!(page, vec![alice(), bob()]);
+        let page: Vec<AccountId> = contract.get_whitelist(U64(2), U64(2)).into_iter().map(|r| r.account_id).collect();
+        assert_eq!(page, vec![carol()]);
+        assert!(contract.get_whitelist(U64(3), U64(2)).is_empty());
+
+        contract.remove_from_whitelist(alice());
+        assert_eq!(contract.get_whitelist(U64(0), U64(10)).len(), 2);
+    }
+
+    fn requestor_bounds() -> oracle_config::RequestorBounds {
+        oracle_config::RequestorBounds {
+            min_validity_bond: U128(50),
+            max_validity_bond: U128(1000),
+            min_challenge_window_duration: U64(500),
+            max_challenge_window_duration: U64(5000),
+            max_outcomes: 16,
+            min_final_arbitrator_invoke_amount: U128(100),
+            max_final_arbitrator_invoke_amount: U128(10000),
+        }
+    }
+
+    fn overrides(validity_bond: u128) -> RequestorOverrides {
+        RequestorOverrides {
+            validity_bond: Some(U128(validity_bond)),
+            min_initial_challenge_window_duration: None,
+            default_challenge_window_duration: None,
+            max_outcomes: Some(12),
+            final_arbitrator_invoke_amount: None,
+        }
+    }
+
+    #[test]
+    fn whitelist_add_with_overrides() {
+        testing_env!(get_context(gov()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let mut contract = Contract::new(None, c);
+
+        let mut requestor = registry_entry(alice());
+        requestor.overrides = Some(overrides(500));
+        contract.add_to_whitelist(requestor);
+        assert_eq!(contract.get_requestor(alice()).unwrap().overrides, Some(overrides(500)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Validity bond override should be between 50 and 1000")]
+    fn whitelist_add_overrides_out_of_bounds() {
+        testing_env!(get_context(gov()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let mut contract = Contract::new(None, c);
+
+        let mut requestor = registry_entry(alice());
+        requestor.overrides = Some(overrides(1001));
+        contract.add_to_whitelist(requestor);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requestor overrides aren't enabled")]
+    fn whitelist_add_overrides_without_bounds() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config());
+
+        let mut requestor = registry_entry(alice());
+        requestor.overrides = Some(overrides(500));
+        contract.add_to_whitelist(requestor);
+    }
+
+    #[test]
+    fn config_for_requestor_clamps_to_bounds() {
+        testing_env!(get_context(gov()));
+        let mut c = config();
+        c.requestor_bounds = Some(requestor_bounds());
+        let mut requestor = registry_entry(alice());
+        requestor.overrides = Some(overrides(500));
+
+        // bounds tightened after the requestor was whitelisted
+        let mut bounds = requestor_bounds();
+        bounds.max_validity_bond = U128(200);
+        bounds.max_outcomes = 10;
+        c.requestor_bounds = Some(bounds);
+
+        let requestor_config = c.for_requestor(&requestor);
+        assert_eq!(requestor_config.validity_bond, U128(200));
+        assert_eq!(requestor_config.max_outcomes, 10);
+        assert_eq!(requestor_config.default_challenge_window_duration, c.default_challenge_window_duration);
     }
 }
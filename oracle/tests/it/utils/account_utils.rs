@@ -102,6 +102,11 @@ impl TestAccount {
                     challenge_period: U64(1000),
                     data_type: DataRequestDataType::String,
                     creator: self.account.account_id(),
+                    reveal_period: None,
+                    tolerance: None,
+                    settlement_time: None,
+                    expiry_fallback: None,
+                    target_contract: None,
                 }
             }).to_string().as_bytes(),
             DEFAULT_GAS,
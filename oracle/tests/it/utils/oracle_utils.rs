@@ -1,5 +1,5 @@
 use crate::utils::*;
-use oracle::oracle_config::OracleConfig;
+use oracle::oracle_config::{ OracleConfig, BondEscalation, ArbitrationFallback };
 use oracle::{Requestor};
 use oracle::fee_config::FeeConfig;
 
@@ -12,7 +12,9 @@ fn new_registry_entry(contract_id: String, stake_multiplier: Option<u16>) -> Req
         code_base_url: None,
         account_id: contract_id,
         interface_name: "test".to_string(),
-        stake_multiplier
+        stake_multiplier,
+        callback: None,
+        overrides: None
     }
 }
 
@@ -33,6 +35,17 @@ impl OracleUtils {
             default_challenge_window_duration: U64(1000),
             min_initial_challenge_window_duration: U64(1000),
             final_arbitrator_invoke_amount: U128(final_arbitrator_invoke_amount),
+            slash_unrevealed_stake: false,
+            max_settlement_time_offset: U64(10000),
+            request_expiry_duration: U64(5000),
+            bond_escalation: BondEscalation::Multiplier(20_000),
+            arbitration_council: None,
+            arbitration_timeout: U64(0),
+            arbitration_fallback: ArbitrationFallback::LastBondedOutcome,
+            claim_window_duration: U64(5000),
+            treasury: "alice".to_string(),
+            requestor_bounds: None,
+            config_change_delay: U64(0),
             fee: FeeConfig {
                 flux_market_cap: U128(50000),
                 total_value_staked: U128(10000),